@@ -1,5 +1,5 @@
-use anyhow::{anyhow, bail, Context, Result};
-use clap::Parser;
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use log::{info, warn, LevelFilter};
 use memflex::external::OwnedProcess;
 use petgraph::dot;
@@ -10,26 +10,21 @@ use std::{
     thread::sleep,
     time::{Duration, Instant},
 };
+use toml_edit::Value;
 use uedumper::{
-    codegen::{Codegen, RustCodegen, RustOptions},
-    Config, DumperOptions, External,
+    codegen::{Codegen, CppCodegen, CppOptions, RustCodegen, RustOptions},
+    Config, DumperOptions, External, Recorder, Sdk, Snapshot,
 };
 
 /// Dumpes unreal engine SDK externally by accessing game memory through WinAPI.
 #[derive(Parser)]
 #[clap(version, author)]
 struct Args {
-    /// process id of the game
-    #[clap(short = 'p', long = "process-id")]
-    pid: Option<u32>,
+    #[clap(subcommand)]
+    command: Option<Command>,
 
-    /// FNamePool offset
-    #[clap(short = 'N', long)]
-    names: Option<String>,
-
-    /// TUObjectArray offset
-    #[clap(short = 'O', long)]
-    objects: Option<String>,
+    #[clap(flatten)]
+    target: TargetArgs,
 
     /// specifies packages to merge together in format `target:consumer`
     #[clap(short = 'M', long)]
@@ -39,10 +34,6 @@ struct Args {
     #[clap(short = 'd', long = "dry-run")]
     dry: bool,
 
-    /// use glam structures instead of auto-generated
-    #[clap(short = 'm', long)]
-    glam: bool,
-
     /// do not try to eliminate dependency cycles
     #[clap(short = 'b', long)]
     allow_cycles: bool,
@@ -51,10 +42,105 @@ struct Args {
     #[clap(short = 'g', long)]
     dot: Option<String>,
 
+    /// save reflected SDK model as json
+    #[clap(short = 'j', long)]
+    json: Option<String>,
+
+    #[clap(flatten)]
+    codegen: CodegenArgs,
+}
+
+#[derive(clap::Args)]
+struct CodegenArgs {
+    /// use glam structures instead of auto-generated
+    #[clap(short = 'm', long)]
+    glam: bool,
+
     /// output folder for the generated SDK
     #[clap(short = 'o', long)]
     output: Option<String>,
 
+    /// language of the generated SDK
+    #[clap(short = 'l', long, value_enum, default_value_t = Language::Rust)]
+    lang: Language,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Language {
+    Rust,
+    Cpp,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Dumps the game and records every memory region that was read into a snapshot file
+    Capture {
+        #[clap(flatten)]
+        target: TargetArgs,
+
+        /// snapshot file path
+        #[clap(short = 'o', long, default_value = "game.snap")]
+        output: String,
+    },
+    /// Locates FNamePool and TUObjectArray in the game module
+    Discover {
+        #[clap(flatten)]
+        target: TargetArgs,
+
+        /// write discovered offsets into the config file
+        #[clap(short = 'w', long, requires = "config")]
+        write: bool,
+    },
+    /// Generates SDK from a model saved with `--json` without attaching to the game
+    Generate {
+        /// path to the saved SDK model
+        #[clap(short = 'f', long)]
+        from: String,
+
+        #[clap(flatten)]
+        codegen: CodegenArgs,
+    },
+    /// Compares two SDK models saved with `--json`
+    Diff {
+        /// model of the older game build
+        old: String,
+
+        /// model of the newer game build
+        new: String,
+
+        /// print differences as json
+        #[clap(short = 'j', long)]
+        json: bool,
+    },
+    /// Infers engine structure offsets and prints a ready to use config
+    Probe {
+        #[clap(flatten)]
+        target: TargetArgs,
+
+        /// write config to the file instead of printing it
+        #[clap(short = 'o', long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(clap::Args)]
+struct TargetArgs {
+    /// process id of the game
+    #[clap(short = 'p', long = "process-id")]
+    pid: Option<u32>,
+
+    /// read game memory from a snapshot file instead of a running process
+    #[clap(short = 's', long, conflicts_with = "pid")]
+    snapshot: Option<String>,
+
+    /// FNamePool offset
+    #[clap(short = 'N', long)]
+    names: Option<String>,
+
+    /// TUObjectArray offset
+    #[clap(short = 'O', long)]
+    objects: Option<String>,
+
     /// config file path
     #[clap(short = 'c', long)]
     config: Option<String>,
@@ -86,14 +172,38 @@ fn get_process_id(arg_id: Option<u32>) -> Result<u32> {
     }
 }
 
-fn get_offset(from_cfg: Option<usize>, from_arg: &Option<String>, name: &str) -> Result<usize> {
+fn get_offset(from_cfg: Option<usize>, from_arg: &Option<String>) -> Result<Option<usize>> {
     if let Some(offset) = from_cfg {
-        Ok(offset)
+        Ok(Some(offset))
     } else if let Some(arg) = from_arg {
-        Ok(parse_hex_arg(arg)?)
+        Ok(Some(parse_hex_arg(arg)?))
     } else {
-        bail!("Missing {name} offset")
+        Ok(None)
+    }
+}
+
+/// Takes offsets from the arguments or the config, missing ones are discovered.
+fn resolve_offsets(
+    target: &TargetArgs,
+    config: &Config,
+    external: &dyn External,
+    base: usize,
+) -> Result<(usize, usize)> {
+    let offsets = config.offsets.as_ref();
+    let names = get_offset(offsets.and_then(|o| o.names), &target.names)?;
+    let objects = get_offset(offsets.and_then(|o| o.objects), &target.objects)?;
+
+    if let (Some(names), Some(objects)) = (names, objects) {
+        return Ok((names, objects));
     }
+
+    info!("Offsets are missing, trying to discover them");
+    let discovered = uedumper::discover_offsets(external, base, config)?;
+
+    Ok((
+        names.unwrap_or(discovered.names),
+        objects.unwrap_or(discovered.objects),
+    ))
 }
 
 fn main() -> Result<()> {
@@ -110,51 +220,55 @@ fn main() -> Result<()> {
         .parse_default_env()
         .init();
 
+    match args.command {
+        Some(Command::Capture {
+            ref target,
+            ref output,
+        }) => capture(target, output),
+        Some(Command::Discover { ref target, write }) => discover(target, write),
+        Some(Command::Generate {
+            ref from,
+            ref codegen,
+        }) => {
+            let sdk = Sdk::load_json(from)?;
+            info!("Loaded SDK model from {from}");
+            generate(&sdk, codegen)
+        }
+        Some(Command::Diff {
+            ref old,
+            ref new,
+            json,
+        }) => diff(old, new, json),
+        Some(Command::Probe {
+            ref target,
+            ref output,
+        }) => probe(target, output),
+        None => dump(&args),
+    }
+}
+
+fn dump(args: &Args) -> Result<()> {
     if args.dry {
         warn!("Performing a dry run, no SDK will be written to the disk");
         sleep(Duration::from_millis(1000));
     }
 
-    let config = fetch_offsets(&args.config)?;
+    let config = fetch_offsets(&args.target.config)?;
+    let (external, base, process_id) = attach(&args.target)?;
+    let (names, objects) = resolve_offsets(&args.target, &config, &*external, base)?;
     let options = DumperOptions {
-        objects: get_offset(
-            config.offsets.as_ref().and_then(|o| o.objects),
-            &args.objects,
-            "TUObjectArray",
-        )?,
-        names: get_offset(
-            config.offsets.as_ref().and_then(|o| o.names),
-            &args.names,
-            "FNamePool",
-        )?,
+        objects,
+        names,
         merge: parse_merge_args(&args.merge)?,
         allow_cycles: args.allow_cycles,
-        process_id: get_process_id(args.pid)?,
+        process_id,
     };
 
-    #[cfg(windows)]
-    use memflex::types::win::{PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
-
-    #[cfg(windows)]
-    let proc = memflex::external::open_process_by_id(
-        options.process_id,
-        false,
-        PROCESS_VM_READ | PROCESS_QUERY_INFORMATION,
-    )?;
-
-    #[cfg(unix)]
-    let proc = memflex::external::find_process_by_id(options.process_id)?;
-
-    let module = proc
-        .modules()?
-        .find(|m| m.name.ends_with("exe"))
-        .ok_or(anyhow!("Failed to find process executable image"))?;
-
     let start = Instant::now();
-    let sdk = uedumper::run(options, config, Box::new(Wrapper(proc)), module.base as _)?;
+    let sdk = uedumper::run(options, config, external, base)?;
     info!("Dumper finished in {:.2?}", start.elapsed());
 
-    if let Some(mut path) = args.dot {
+    if let Some(mut path) = args.dot.clone() {
         if !path.ends_with(".dot") {
             path = format!("{path}.dot")
         }
@@ -169,22 +283,152 @@ fn main() -> Result<()> {
         info!("Saved dependency graph file as {path}");
     }
 
+    if let Some(path) = &args.json {
+        sdk.save_json(path)?;
+        info!("Saved SDK model as {path}");
+    }
+
     if !args.dry {
-        let start = Instant::now();
+        generate(&sdk, &args.codegen)?;
+    }
+
+    Ok(())
+}
+
+fn generate(sdk: &Sdk, args: &CodegenArgs) -> Result<()> {
+    let start = Instant::now();
+
+    let path = args.output.clone().unwrap_or("usdk".into()).into();
+    match args.lang {
+        Language::Rust => {
+            let options = RustOptions {
+                path,
+                glam: args.glam,
+            };
+            RustCodegen::new(sdk, &options)?.generate()?;
+        }
+        Language::Cpp => {
+            let options = CppOptions { path };
+            CppCodegen::new(sdk, &options)?.generate()?;
+        }
+    }
+
+    info!("Sdk generation finished in {:.2?}", start.elapsed());
+    Ok(())
+}
+
+fn capture(target: &TargetArgs, output: &str) -> Result<()> {
+    let config = fetch_offsets(&target.config)?;
+    let (external, base, process_id) = attach(target)?;
+
+    // Discovery reads must end up in the snapshot as well, so it can be replayed without offsets.
+    let recorder = Recorder::new(external, base);
+    let snapshot = recorder.snapshot();
+
+    let (names, objects) = resolve_offsets(target, &config, &recorder, base)?;
+    let options = DumperOptions {
+        objects,
+        names,
+        merge: HashMap::new(),
+        allow_cycles: true,
+        process_id,
+    };
+
+    let start = Instant::now();
+    uedumper::run(options, config, Box::new(recorder), base)?;
+    info!("Dumper finished in {:.2?}", start.elapsed());
+
+    let snapshot = snapshot.lock().unwrap();
+    snapshot.save(output)?;
+    info!(
+        "Saved snapshot with {:#X} bytes of memory as {output}",
+        snapshot.size()
+    );
+
+    Ok(())
+}
+
+fn discover(target: &TargetArgs, write: bool) -> Result<()> {
+    let config = fetch_offsets(&target.config)?;
+    let (external, base, _) = attach(target)?;
+
+    let offsets = uedumper::discover_offsets(&*external, base, &config)?;
+    println!("Names = {:#X}", offsets.names);
+    println!("Objects = {:#X}", offsets.objects);
+
+    if let (true, Some(path)) = (write, &target.config) {
+        let mut doc = fs::read_to_string(path)?.parse::<toml_edit::Document>()?;
+        let table = doc["Offsets"].or_insert(toml_edit::table());
+        table["Names"] = toml_edit::value(format!("{:#X}", offsets.names).parse::<Value>()?);
+        table["Objects"] = toml_edit::value(format!("{:#X}", offsets.objects).parse::<Value>()?);
+
+        fs::write(path, doc.to_string())?;
+        info!("Saved offsets to {path}");
+    }
 
-        let options = RustOptions {
-            path: args.output.unwrap_or("usdk".into()).into(),
-            glam: args.glam,
-        };
-        let codegen = RustCodegen::new(&sdk, &options)?;
-        codegen.generate()?;
+    Ok(())
+}
+
+fn diff(old: &str, new: &str, json: bool) -> Result<()> {
+    let diff = uedumper::diff_sdk(&Sdk::load_json(old)?, &Sdk::load_json(new)?);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(())
+}
 
-        info!("Sdk generation finished in {:.2?}", start.elapsed());
+fn probe(target: &TargetArgs, output: &Option<String>) -> Result<()> {
+    let fallback = fetch_offsets(&target.config)?;
+    let (external, base, _) = attach(target)?;
+    let (names, objects) = resolve_offsets(target, &fallback, &*external, base)?;
+
+    let probed = uedumper::probe_config(&*external, base, names, objects, &fallback)?;
+    let text = probed.to_toml()?;
+    if let Some(path) = output {
+        fs::write(path, text)?;
+        info!("Saved probed config as {path}");
+    } else {
+        print!("{text}");
     }
 
     Ok(())
 }
 
+fn attach(target: &TargetArgs) -> Result<(Box<dyn External>, usize, Option<u32>)> {
+    if let Some(path) = &target.snapshot {
+        let snapshot = Snapshot::load(path)?;
+        info!("Loaded snapshot from {path}");
+
+        let base = snapshot.base();
+        return Ok((Box::new(snapshot), base, None));
+    }
+
+    let process_id = get_process_id(target.pid)?;
+
+    #[cfg(windows)]
+    use memflex::types::win::{PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+
+    #[cfg(windows)]
+    let proc = memflex::external::open_process_by_id(
+        process_id,
+        false,
+        PROCESS_VM_READ | PROCESS_QUERY_INFORMATION,
+    )?;
+
+    #[cfg(unix)]
+    let proc = memflex::external::find_process_by_id(process_id)?;
+
+    let module = proc
+        .modules()?
+        .find(|m| m.name.ends_with("exe"))
+        .ok_or(anyhow!("Failed to find process executable image"))?;
+
+    Ok((Box::new(Wrapper(proc)), module.base as _, Some(process_id)))
+}
+
 fn fetch_offsets(config: &Option<String>) -> Result<Config> {
     if let Some(path) = config {
         let text = fs::read_to_string(path)?;
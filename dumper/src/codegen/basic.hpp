@@ -0,0 +1,123 @@
+template<typename T>
+struct TArray {
+    T* Data;
+    int32_t Num;
+    int32_t Max;
+};
+static_assert(sizeof(TArray<uint8_t>) == 0x10);
+
+struct FString {
+    TArray<char16_t> Data;
+};
+static_assert(sizeof(FString) == 0x10);
+
+// Display string can be read through `TTextData`, see `ucore::FText`.
+struct FText {
+    void* Data;
+    void* Controller;
+    uint32_t Flags;
+    uint8_t Pad_14[0x4];
+};
+static_assert(sizeof(FText) == 0x18);
+
+struct FWeakObjectPtr {
+    int32_t ObjectIndex;
+    int32_t ObjectSerialNumber;
+};
+static_assert(sizeof(FWeakObjectPtr) == 0x8);
+
+template<typename T>
+struct TWeakObjectPtr {
+    FWeakObjectPtr Weak;
+};
+
+struct FScriptDelegate {
+    FWeakObjectPtr Object;
+    FName FunctionName;
+};
+
+struct FMulticastScriptDelegate {
+    TArray<FScriptDelegate> InvocationList;
+};
+static_assert(sizeof(FMulticastScriptDelegate) == 0x10);
+
+// Invocation list is stored outside of the object.
+struct FSparseDelegate {
+    bool bIsBound;
+};
+
+struct FSoftObjectPath {
+    FName AssetPathName;
+    FString SubPathString;
+};
+
+struct FSoftObjectPtr {
+    FWeakObjectPtr Weak;
+    int32_t TagAtLastTest;
+    uint8_t Pad_C[0x4];
+    FSoftObjectPath ObjectId;
+};
+
+template<typename T>
+struct TSoftObjectPtr {
+    FSoftObjectPtr Ptr;
+};
+
+// `T` is the base class of the referenced class.
+template<typename T>
+struct TSoftClassPtr {
+    FSoftObjectPtr Ptr;
+};
+
+struct FUniqueObjectGuid {
+    uint32_t Guid[4];
+};
+
+struct FLazyObjectPtr {
+    FWeakObjectPtr Weak;
+    int32_t TagAtLastTest;
+    FUniqueObjectGuid ObjectId;
+};
+static_assert(sizeof(FLazyObjectPtr) == 0x1C);
+
+template<typename T>
+struct TLazyObjectPtr {
+    FLazyObjectPtr Ptr;
+};
+
+template<typename T>
+struct TScriptInterface {
+    void* Object;
+    void* Interface;
+};
+static_assert(sizeof(TScriptInterface<void>) == 0x10);
+
+// Path goes from the innermost field up to the package.
+struct FFieldPath {
+    void* ResolvedField;
+    FWeakObjectPtr ResolvedOwner;
+    TArray<FName> Path;
+};
+static_assert(sizeof(FFieldPath) == 0x20);
+
+template<typename T>
+struct TSet {
+    uint8_t Data[0x50];
+};
+
+template<typename K, typename V>
+struct TMap {
+    uint8_t Data[0x50];
+};
+#pragma pack(pop)
+
+struct UObject;
+
+// Must be provided by the user, looks up an object by its full name, i.e. `Engine.Actor`.
+UObject* FindObject(const char* fqn);
+
+inline void ProcessEvent(const void* object, UObject* function, void* params) {
+    using Fn = void (*)(const void*, UObject*, void*);
+    (*reinterpret_cast<Fn* const*>(object))[PROCESS_EVENT_INDEX](object, function, params);
+}
+}
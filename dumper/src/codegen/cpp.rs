@@ -0,0 +1,642 @@
+use super::Codegen;
+use crate::{
+    engine::{FunctionFlags, PropertyFlags},
+    sdk::{
+        Enum, Field, FieldOptions, Function, FunctionArg, Object, Package, PropertyKind, Sdk,
+        Struct,
+    },
+    utils::{Bitfield, NameDedup},
+};
+use anyhow::Result;
+use petgraph::Direction::Outgoing;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fmt::Write as WriteFmt,
+    fs::{self, OpenOptions},
+    io::{BufWriter, Write as WriteIo},
+    iter::successors,
+    path::PathBuf,
+};
+use ucore::Fqn;
+
+pub struct CppOptions {
+    pub path: PathBuf,
+}
+
+/// Generates one header per package, structs are packed and padded explicitly
+/// so their layout doesn't depend on the compiler.
+pub struct CppCodegen<'a> {
+    options: &'a CppOptions,
+    sdk: &'a Sdk,
+}
+
+impl<'a> Codegen<'a> for CppCodegen<'a> {
+    type Options = CppOptions;
+
+    fn new(sdk: &'a Sdk, options: &'a Self::Options) -> Result<Self> {
+        Ok(Self { options, sdk })
+    }
+
+    fn generate(&self) -> Result<()> {
+        fs::create_dir_all(&self.options.path)?;
+        self.generate_basic()?;
+
+        for pkg in self.sdk.packages.node_weights() {
+            self.generate_package(pkg)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CppCodegen<'_> {
+    fn open(&self, name: &str) -> Result<BufWriter<fs::File>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(self.options.path.join(name))?;
+
+        Ok(BufWriter::new(file))
+    }
+
+    fn generate_basic(&self) -> Result<()> {
+        let mut w = self.open("Basic.hpp")?;
+        let config = &self.sdk.config;
+
+        writeln!(w, "#pragma once\n#include <cstddef>\n#include <cstdint>\n")?;
+        writeln!(w, "namespace SDK {{")?;
+        writeln!(
+            w,
+            "constexpr size_t PROCESS_EVENT_INDEX = {:#X};\n",
+            config.process_event
+        )?;
+        writeln!(w, "#pragma pack(push, 0x1)")?;
+
+        let fname = &config.fname;
+        let mut members = [
+            Some((fname.index, "ComparisonIndex")),
+            fname.display_index.map(|o| (o, "DisplayIndex")),
+            fname.number.map(|o| (o, "Number")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        members.sort_unstable();
+
+        writeln!(w, "struct FName {{")?;
+        let mut offset = 0;
+        for (member_offset, name) in members {
+            write_padding(&mut w, offset, member_offset)?;
+            writeln!(w, "    int32_t {name}; // {member_offset:#X}(0x4)")?;
+            offset = member_offset + 4;
+        }
+        write_padding(&mut w, offset, fname.size)?;
+        writeln!(
+            w,
+            "}};\nstatic_assert(sizeof(FName) == {:#X});\n",
+            fname.size
+        )?;
+
+        w.write_all(include_str!("basic.hpp").as_bytes())?;
+
+        Ok(())
+    }
+
+    fn generate_package(&self, pkg: &Package) -> Result<()> {
+        let mut w = self.open(&format!("{}.hpp", pkg.ident))?;
+
+        writeln!(w, "#pragma once\n#include \"Basic.hpp\"")?;
+        for dep in self
+            .sdk
+            .packages
+            .neighbors_directed(self.sdk.indices[&pkg.ident], Outgoing)
+        {
+            let dep = self.sdk.packages.node_weight(dep).unwrap();
+            writeln!(w, "#include \"{}.hpp\"", dep.ident)?;
+        }
+        writeln!(w, "\nnamespace SDK {{")?;
+
+        let structs = self.sort_structs(pkg);
+        for ustruct in structs.iter() {
+            writeln!(w, "struct {};", ustruct.ident)?;
+        }
+        writeln!(w)?;
+
+        for obj in pkg.objects.iter() {
+            if let Object::Enum(uenum) = &**obj {
+                self.generate_enum(&mut w, uenum)?;
+            }
+        }
+
+        writeln!(w, "#pragma pack(push, 0x1)")?;
+        let mut definitions = String::new();
+        for ustruct in structs.iter() {
+            self.generate_struct(&mut w, ustruct, &mut definitions)?;
+        }
+        writeln!(w, "#pragma pack(pop)\n")?;
+
+        write!(w, "{definitions}")?;
+        writeln!(w, "}}")?;
+
+        Ok(())
+    }
+
+    /// Orders structs so parents and inline fields from the same package are defined first.
+    fn sort_structs<'p>(&self, pkg: &'p Package) -> Vec<&'p Struct> {
+        fn visit<'p>(
+            ustruct: &'p Struct,
+            pkg: &'p Package,
+            visited: &mut HashSet<Fqn>,
+            out: &mut Vec<&'p Struct>,
+        ) {
+            if !visited.insert(ustruct.fqn) {
+                return;
+            }
+
+            let inline = ustruct.fields.iter().filter_map(|f| match f {
+                Field::Property { kind, .. } => successors(Some(kind), |k| match k {
+                    PropertyKind::Array { kind, .. } => Some(kind),
+                    _ => None,
+                })
+                .last()
+                .and_then(|k| match k {
+                    PropertyKind::Inline(fqn) => Some(*fqn),
+                    _ => None,
+                }),
+                Field::Bitfields(_) => None,
+            });
+
+            for dep in ustruct.parent.into_iter().chain(inline) {
+                let dep = pkg.objects.iter().find_map(|o| match &**o {
+                    Object::Class(s) | Object::Struct(s) if s.fqn == dep => Some(s),
+                    _ => None,
+                });
+
+                if let Some(dep) = dep {
+                    visit(dep, pkg, visited, out);
+                }
+            }
+
+            out.push(ustruct);
+        }
+
+        let mut visited = HashSet::new();
+        let mut out = vec![];
+        for obj in pkg.objects.iter() {
+            if let Object::Class(ustruct) | Object::Struct(ustruct) = &**obj {
+                visit(ustruct, pkg, &mut visited, &mut out);
+            }
+        }
+
+        out
+    }
+
+    fn generate_enum(&self, w: &mut dyn WriteIo, uenum: &Enum) -> Result<()> {
+        let Enum {
+            fqn,
+            ident,
+            layout,
+            variants,
+        } = uenum;
+
+        let bits = layout.size * 8;
+        let mask = u64::MAX >> (64 - bits);
+
+        writeln!(w, "// `{fqn}`")?;
+        writeln!(w, "// Size = {}", layout.size)?;
+        writeln!(w, "enum class {ident} : uint{bits}_t {{")?;
+
+        let mut used_names = HashSet::new();
+        for (name, value) in variants.iter() {
+            let name = escape_keyword(name);
+            let suffix = if used_names.contains(&name) {
+                Cow::from(format!("_{value}"))
+            } else {
+                Cow::from("")
+            };
+
+            if *value >= 0 {
+                writeln!(w, "    {name}{suffix} = {value},")?;
+            } else {
+                writeln!(w, "    {name}{suffix} = {:#X},", *value as u64 & mask)?;
+            }
+
+            used_names.insert(name);
+        }
+
+        writeln!(w, "}};\n")?;
+
+        Ok(())
+    }
+
+    fn generate_struct(
+        &self,
+        w: &mut dyn WriteIo,
+        ustruct: &Struct,
+        definitions: &mut String,
+    ) -> Result<()> {
+        let Struct {
+            fqn,
+            parent,
+            ident,
+            layout,
+            fields,
+            shrink,
+            functions,
+            ..
+        } = ustruct;
+
+        writeln!(w, "// `{fqn}`")?;
+        writeln!(
+            w,
+            "// Size = {:#X}({:#X}), Alignment = {:#X}{}",
+            layout.size,
+            layout.get_aligned_size(),
+            layout.align,
+            if let Some(size) = shrink.get() {
+                Cow::from(format!(", Shrunk = {size:#X}"))
+            } else {
+                Cow::from("")
+            }
+        )?;
+
+        let mut offset = 0;
+        if let Some(ref parent_fqn) = parent {
+            let (Object::Class(parent) | Object::Struct(parent)) =
+                &*self.sdk.lookup(parent_fqn).unwrap().ptr
+            else {
+                unreachable!()
+            };
+
+            offset = parent
+                .shrink
+                .get()
+                .unwrap_or(parent.layout.get_aligned_size());
+
+            let chain = successors(Some(*parent_fqn), |fqn| {
+                let (Object::Class(parent) | Object::Struct(parent)) =
+                    &*self.sdk.lookup(fqn).unwrap().ptr
+                else {
+                    unreachable!()
+                };
+
+                parent.parent
+            })
+            .map(|fqn| self.sdk.lookup(&fqn).unwrap().ptr.ident())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+            writeln!(w, "// Inheritance: {chain}")?;
+            writeln!(w, "struct {ident} : public {} {{", parent.ident)?;
+        } else {
+            writeln!(w, "struct {ident} {{")?;
+        }
+
+        let mut dedup = NameDedup::default();
+        let mut asserts = vec![];
+
+        for field in fields {
+            match field {
+                Field::Property {
+                    name,
+                    kind,
+                    options:
+                        FieldOptions {
+                            offset: field_offset,
+                            elem_size,
+                            array_dim,
+                        },
+                } => {
+                    let name = dedup.entry(&escape_keyword(name)).into_owned();
+                    let Some(decl) = self.declare(kind, &name) else {
+                        continue;
+                    };
+
+                    let total_size = *elem_size * *array_dim;
+                    // Moving the field would break its offset, i.e. fields of a union.
+                    if *field_offset < offset {
+                        writeln!(
+                            w,
+                            "    // {decl}; // {field_offset:#X}({total_size:#X}) overlaps previous field"
+                        )?;
+                        continue;
+                    }
+
+                    write_padding(w, offset, *field_offset)?;
+                    offset = *field_offset;
+                    writeln!(w, "    {decl}; // {offset:#X}({total_size:#X})")?;
+                    asserts.push((name, offset));
+
+                    offset += total_size;
+                }
+                Field::Bitfields(group) => {
+                    write_padding(w, offset, group.offset)?;
+
+                    let mut items = group.items.iter().collect::<Vec<_>>();
+                    items.sort_by_key(|b| b.offset);
+
+                    let mut bit = 0;
+                    for Bitfield { name, offset, len } in items {
+                        if *offset > bit {
+                            writeln!(w, "    uint8_t : {};", *offset - bit)?;
+                        }
+
+                        let name = dedup.entry(&escape_keyword(name)).into_owned();
+                        writeln!(
+                            w,
+                            "    uint8_t {name} : {len}; // {:#X}({offset}:{len})",
+                            group.offset
+                        )?;
+                        bit = *offset + *len;
+                    }
+
+                    if bit < 8 {
+                        writeln!(w, "    uint8_t : {};", 8 - bit)?;
+                    }
+
+                    // Groups are always 1 byte.
+                    offset = group.offset + 1;
+                }
+            }
+        }
+
+        let struct_size = shrink.get().unwrap_or(layout.get_aligned_size());
+        write_padding(w, offset, struct_size)?;
+
+        // Delegate signatures can't be called directly.
+        let funcs = functions.borrow();
+        let funcs = funcs
+            .iter()
+            .filter(|f| !f.flags.contains(FunctionFlags::Delegate))
+            .collect::<Vec<_>>();
+        if !funcs.is_empty() {
+            writeln!(w)?;
+        }
+
+        for func in funcs.iter() {
+            self.write_function(w, ustruct, func, &mut dedup, definitions)?;
+        }
+
+        writeln!(w, "}};")?;
+        writeln!(w, "static_assert(sizeof({ident}) == {struct_size:#X});")?;
+        for (name, offset) in asserts {
+            writeln!(
+                w,
+                "static_assert(offsetof({ident}, {name}) == {offset:#X});"
+            )?;
+        }
+        writeln!(w)?;
+
+        Ok(())
+    }
+
+    /// Declares the method in the struct body and appends its definition to `definitions`,
+    /// definitions are written after the packed structs so parameters keep their natural layout.
+    fn write_function(
+        &self,
+        w: &mut dyn WriteIo,
+        ustruct: &Struct,
+        func: &Function,
+        dedup: &mut NameDedup,
+        definitions: &mut String,
+    ) -> Result<()> {
+        let Function {
+            ident: func_ident,
+            args,
+            flags,
+            fqn,
+            ..
+        } = func;
+        let ident = &ustruct.ident;
+
+        let mut argd = NameDedup::default();
+        let mut args = args.to_vec();
+        for arg in args.iter_mut() {
+            arg.name = argd.entry(&escape_keyword(&arg.name)).into_owned();
+        }
+        drop(argd);
+
+        let mut params = String::new();
+        let mut fargs = vec![];
+        let mut ret = vec![];
+
+        for arg in args.iter() {
+            let (Some(ty), Some(align)) =
+                (self.stringify_type(&arg.kind), self.kind_align(&arg.kind))
+            else {
+                writeln!(w, "    // Skipped `{fqn}`, unsupported parameter type")?;
+                return Ok(());
+            };
+
+            writeln!(params, "        alignas({align:#X}) {ty} {};", arg.name)?;
+            if is_inout(arg) {
+                fargs.push((format!("{ty}&").into(), &arg.name));
+            } else if arg.flags.contains(PropertyFlags::OutParm) {
+                ret.push((ty, &arg.name));
+            } else {
+                fargs.push((ty, &arg.name));
+            }
+        }
+
+        let name = dedup.entry(func_ident);
+        let result = format!("{ident}_{name}Result");
+        let ret_ty = match ret.len() {
+            0 => Cow::from("void"),
+            1 => ret[0].0.clone(),
+            _ => {
+                writeln!(definitions, "struct {result} {{")?;
+                for (ty, name) in ret.iter() {
+                    writeln!(definitions, "    {ty} {name};")?;
+                }
+                writeln!(definitions, "}};\n")?;
+
+                Cow::from(result.as_str())
+            }
+        };
+
+        let fargs = fargs
+            .iter()
+            .map(|(ty, name)| format!("{ty} {name}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let is_static = flags.contains(FunctionFlags::Static);
+        writeln!(
+            w,
+            "    {}{ret_ty} {name}({fargs});",
+            if is_static { "static " } else { "" }
+        )?;
+
+        writeln!(definitions, "inline {ret_ty} {ident}::{name}({fargs}) {{")?;
+        writeln!(
+            definitions,
+            "    static UObject* Function = FindObject(\"{fqn}\");"
+        )?;
+        // Static functions are called on the class default object.
+        if is_static {
+            let class = ustruct.fqn.to_string();
+            let cdo = match class.rsplit_once('.') {
+                Some((outer, name)) => format!("{outer}.Default__{name}"),
+                None => format!("Default__{class}"),
+            };
+            writeln!(
+                definitions,
+                "    static UObject* Default = FindObject(\"{cdo}\");"
+            )?;
+        }
+        writeln!(definitions, "\n    struct {{\n{params}    }} Params{{}};")?;
+        for arg in args
+            .iter()
+            .filter(|a| !a.flags.contains(PropertyFlags::OutParm) || is_inout(a))
+        {
+            writeln!(definitions, "    Params.{0} = {0};", arg.name)?;
+        }
+        writeln!(
+            definitions,
+            "    ProcessEvent({}, Function, &Params);",
+            if is_static { "Default" } else { "this" }
+        )?;
+        for arg in args.iter().filter(|a| is_inout(a)) {
+            writeln!(definitions, "    {0} = Params.{0};", arg.name)?;
+        }
+
+        match ret.len() {
+            0 => {}
+            1 => writeln!(definitions, "    return Params.{};", ret[0].1)?,
+            _ => {
+                let fields = ret
+                    .iter()
+                    .map(|(_, name)| format!("Params.{name}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(definitions, "    return {result}{{ {fields} }};")?;
+            }
+        }
+        writeln!(definitions, "}}\n")?;
+
+        Ok(())
+    }
+
+    fn declare(&self, kind: &PropertyKind, name: &str) -> Option<String> {
+        match kind {
+            PropertyKind::Array { kind, size } => self.declare(kind, &format!("{name}[{size:#X}]")),
+            kind => Some(format!("{} {name}", self.stringify_type(kind)?)),
+        }
+    }
+
+    fn stringify_type(&self, kind: &PropertyKind) -> Option<Cow<'static, str>> {
+        let repr: Cow<str> = match kind {
+            PropertyKind::Bool => "bool".into(),
+            PropertyKind::Int8 => "int8_t".into(),
+            PropertyKind::Int16 => "int16_t".into(),
+            PropertyKind::Int32 => "int32_t".into(),
+            PropertyKind::Int64 => "int64_t".into(),
+            PropertyKind::UInt8 => "uint8_t".into(),
+            PropertyKind::UInt16 => "uint16_t".into(),
+            PropertyKind::UInt32 => "uint32_t".into(),
+            PropertyKind::UInt64 => "uint64_t".into(),
+            PropertyKind::Float32 => "float".into(),
+            PropertyKind::Float64 => "double".into(),
+            PropertyKind::Name => "FName".into(),
+            PropertyKind::String => "FString".into(),
+            PropertyKind::Text => "FText".into(),
+            PropertyKind::Ptr(inner) => format!("{}*", self.sdk.lookup(inner)?.ptr.ident()).into(),
+            PropertyKind::Weak(inner) => self.wrap("TWeakObjectPtr", inner)?,
+            PropertyKind::Soft(inner) => self.wrap("TSoftObjectPtr", inner)?,
+            PropertyKind::SoftClass(inner) => self.wrap("TSoftClassPtr", inner)?,
+            PropertyKind::Lazy(inner) => self.wrap("TLazyObjectPtr", inner)?,
+            PropertyKind::Interface(inner) => self.wrap("TScriptInterface", inner)?,
+            PropertyKind::Delegate(_) => "FScriptDelegate".into(),
+            PropertyKind::MulticastDelegate(_) => "FMulticastScriptDelegate".into(),
+            PropertyKind::SparseDelegate(_) => "FSparseDelegate".into(),
+            PropertyKind::FieldPath(_) => "FFieldPath".into(),
+            PropertyKind::Inline(inner) => self.sdk.lookup(inner)?.ptr.ident().to_owned().into(),
+            PropertyKind::Vec(inner) => format!("TArray<{}>", self.stringify_type(inner)?).into(),
+            PropertyKind::Set(inner) => format!("TSet<{}>", self.stringify_type(inner)?).into(),
+            PropertyKind::Map { key, value } => format!(
+                "TMap<{}, {}>",
+                self.stringify_type(key)?,
+                self.stringify_type(value)?
+            )
+            .into(),
+            // C arrays can only be declared as fields, see `declare`.
+            PropertyKind::Array { .. } | PropertyKind::Unknown => return None,
+        };
+
+        Some(repr)
+    }
+
+    /// Object pointer wrapper, i.e. `TWeakObjectPtr<AActor>`.
+    fn wrap(&self, tmpl: &str, inner: &Fqn) -> Option<Cow<'static, str>> {
+        Some(format!("{tmpl}<{}>", self.sdk.lookup(inner)?.ptr.ident()).into())
+    }
+
+    /// Alignment of the type inside of a ProcessEvent parameters struct.
+    fn kind_align(&self, kind: &PropertyKind) -> Option<usize> {
+        let align = match kind {
+            PropertyKind::Bool
+            | PropertyKind::Int8
+            | PropertyKind::UInt8
+            | PropertyKind::SparseDelegate(_) => 1,
+            PropertyKind::Int16 | PropertyKind::UInt16 => 2,
+            PropertyKind::Int32 | PropertyKind::UInt32 | PropertyKind::Float32 => 4,
+            PropertyKind::Name => 4,
+            PropertyKind::Weak(_) | PropertyKind::Lazy(_) | PropertyKind::Delegate(_) => 4,
+            PropertyKind::Int64 | PropertyKind::UInt64 | PropertyKind::Float64 => 8,
+            PropertyKind::Ptr(_)
+            | PropertyKind::Soft(_)
+            | PropertyKind::SoftClass(_)
+            | PropertyKind::Interface(_)
+            | PropertyKind::MulticastDelegate(_)
+            | PropertyKind::FieldPath(_)
+            | PropertyKind::String
+            | PropertyKind::Text
+            | PropertyKind::Vec(_)
+            | PropertyKind::Set(_)
+            | PropertyKind::Map { .. } => 8,
+            PropertyKind::Inline(inner) => self.sdk.lookup(inner)?.ptr.layout().align.max(1),
+            PropertyKind::Array { kind, .. } => self.kind_align(kind)?,
+            PropertyKind::Unknown => return None,
+        };
+
+        Some(align)
+    }
+}
+
+/// Reference parameters are both read and written by the function.
+fn is_inout(arg: &FunctionArg) -> bool {
+    arg.flags
+        .contains(PropertyFlags::OutParm | PropertyFlags::ReferenceParm)
+}
+
+fn write_padding(w: &mut dyn WriteIo, from: usize, to: usize) -> Result<()> {
+    if to > from {
+        writeln!(
+            w,
+            "    uint8_t Pad_{from:X}[{size:#X}]; // {from:#X}({size:#X})",
+            size = to - from
+        )?;
+    }
+
+    Ok(())
+}
+
+fn escape_keyword(ident: &str) -> Cow<'_, str> {
+    #[rustfmt::skip]
+    const KEYWORDS: &[&str] = &[
+        "alignas", "alignof", "and", "asm", "auto", "bool", "break", "case", "catch", "char",
+        "class", "const", "constexpr", "continue", "default", "delete", "do", "double", "else",
+        "enum", "explicit", "export", "extern", "false", "float", "for", "friend", "goto", "if",
+        "inline", "int", "long", "mutable", "namespace", "new", "not", "nullptr", "operator", "or",
+        "private", "protected", "public", "register", "return", "short", "signed", "sizeof",
+        "static", "struct", "switch", "template", "this", "throw", "true", "try", "typedef",
+        "typename", "union", "unsigned", "using", "virtual", "void", "volatile", "while", "xor",
+    ];
+
+    if KEYWORDS.contains(&ident) {
+        Cow::Owned(format!("{ident}_"))
+    } else {
+        Cow::Borrowed(ident)
+    }
+}
@@ -1,24 +1,23 @@
 use super::Codegen;
 use crate::{
+    config::{NamePoolKind, PropertyModel},
     engine::{FunctionFlags, PropertyFlags},
     sdk::{Enum, Field, FieldOptions, Function, Object, Package, PropertyKind, Sdk, Struct},
-    utils::Bitfield,
-    State,
+    utils::{Bitfield, NameDedup},
 };
 use anyhow::Result;
 use petgraph::Direction::Outgoing;
 use std::{
     borrow::Cow,
-    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    collections::HashSet,
     fmt::Write as WriteFmt,
     fs::{self, File, OpenOptions},
-    hash::Hasher,
     io::{BufWriter, Write as WriteIo},
     iter::successors,
     mem::size_of,
     path::{Path, PathBuf},
 };
-use ucore::fqn;
+use ucore::{fqn, Fqn};
 
 pub struct RustOptions {
     pub path: PathBuf,
@@ -56,10 +55,19 @@ impl<'a> Codegen<'a> for RustCodegen<'a> {
         let mut workspace = BufWriter::new(opts.open(path.join("Cargo.toml"))?);
         writeln!(workspace, "{}", include_str!("workspace.toml"))?;
 
+        let fname = &sdk.config.fname;
+        let features = [
+            (fname.display_index.is_some(), "\"case_preserving_name\""),
+            (fname.outline_number(), "\"outline_name_number\""),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, feature)| enabled.then_some(feature))
+        .collect::<Vec<_>>()
+        .join(", ");
         writeln!(
             workspace,
             r#"[workspace.dependencies]
-ucore = {{ path = "../ucore" }}
+ucore = {{ path = "../ucore", features = [{features}] }}
 uproxy = {{ path = "uproxy" }}
 
 memflex = "*""#
@@ -82,6 +90,34 @@ memflex = "*""#
             self.generate_package(pkg, &crates)?;
         }
 
+        const LAYOUT: &str = r#"
+/// Layout of the game the SDK was dumped from, see `ucore::GlobalContext::from_layout`.
+pub fn layout() -> ucore::EngineLayout {
+    ucore::EngineLayout {
+        names: uproxy::NAMES_OFFSET,
+        objects: uproxy::OBJECTS_OFFSET,
+        world: uproxy::WORLD_OFFSET,
+        engine: uproxy::ENGINE_OFFSET,
+        name: ucore::FNameLayout {
+            stride: uproxy::FNAME_STRIDE,
+            blocks: uproxy::FNAME_BLOCKS,
+            header: uproxy::FNAME_ENTRY_HEADER,
+            wide_bit: uproxy::FNAME_WIDE_BIT,
+            len_bit: uproxy::FNAME_LEN_BIT,
+        },
+        object_item: ucore::FUObjectItemLayout {
+            size: uproxy::FUOBJECT_ITEM_SIZE,
+            flags: uproxy::FUOBJECT_ITEM_FLAGS,
+            serial: uproxy::FUOBJECT_ITEM_SERIAL,
+        },
+        ustruct: ucore::UStructLayout {
+            super_struct: uproxy::USTRUCT_SUPER_STRUCT,
+            child_properties: uproxy::USTRUCT_CHILD_PROPERTIES,
+        },
+    }
+}
+"#;
+
         const EPILOG: &str = r#"
 [package]
 name = "usdk"
@@ -92,11 +128,19 @@ edition.workspace = true
 path = "lib.rs"
 
 [dependencies]
+ucore.workspace = true
 uproxy.workspace = true
 "#;
 
         writeln!(workspace, "{EPILOG}")?;
         writeln!(lib, "pub use uproxy;")?;
+        match sdk.config.name_pool {
+            NamePoolKind::FNamePool => writeln!(lib, "{LAYOUT}")?,
+            NamePoolKind::TNameEntryArray => writeln!(
+                lib,
+                "\n// No `layout()`, `ucore` can't read names of `TNameEntryArray`.\n"
+            )?,
+        }
 
         for pkg in sdk.packages.node_weights().map(|v| &*v.ident) {
             writeln!(workspace, "{pkg} = {{ workspace = true, optional = true }}")?;
@@ -142,12 +186,66 @@ glam.workspace = true
             .truncate(true)
             .open(lib)?;
 
-        let config = &State::get().config;
+        let config = &self.sdk.config;
         writeln!(
             lib,
-            "pub const PROCESS_EVENT_INDEX: usize = {:#X};\n",
+            "pub const PROCESS_EVENT_INDEX: usize = {:#X};",
             config.process_event
         )?;
+        writeln!(
+            lib,
+            "pub const FNAME_SIZE: usize = {:#X};",
+            config.fname.size
+        )?;
+
+        // Layout of the game, `usdk::layout()` passes it to `ucore`.
+        let offsets = config.offsets.clone().unwrap_or_default();
+        if config.name_pool == NamePoolKind::FNamePool {
+            let fname = &config.fname;
+            let constants = [
+                ("FNAME_STRIDE", config.stride as usize),
+                ("FNAME_BLOCKS", fname.pool_blocks),
+                ("FNAME_ENTRY_HEADER", fname.header_offset()),
+            ];
+            for (name, value) in constants {
+                writeln!(lib, "pub const {name}: usize = {value:#X};")?;
+            }
+            for (name, value) in [
+                ("FNAME_WIDE_BIT", fname.wide_bit),
+                ("FNAME_LEN_BIT", fname.len_bit),
+            ] {
+                writeln!(lib, "pub const {name}: u32 = {value};")?;
+            }
+        }
+        let constants = [
+            ("FUOBJECT_ITEM_SIZE", config.fuobject_item.size),
+            ("FUOBJECT_ITEM_FLAGS", config.fuobject_item.flags),
+            ("FUOBJECT_ITEM_SERIAL", config.fuobject_item.serial),
+            ("USTRUCT_SUPER_STRUCT", config.ustruct.super_struct),
+        ];
+        for (name, value) in constants {
+            writeln!(lib, "pub const {name}: usize = {value:#X};")?;
+        }
+        let child_properties = match config.property_model {
+            PropertyModel::FField => Some(config.ustruct.children_props),
+            PropertyModel::UProperty => None,
+        };
+        let globals = [
+            ("USTRUCT_CHILD_PROPERTIES", child_properties),
+            ("NAMES_OFFSET", offsets.names),
+            ("OBJECTS_OFFSET", offsets.objects),
+            ("WORLD_OFFSET", offsets.world),
+            ("ENGINE_OFFSET", offsets.engine),
+        ];
+        for (name, value) in globals {
+            match value {
+                Some(value) => {
+                    writeln!(lib, "pub const {name}: Option<usize> = Some({value:#X});")?
+                }
+                None => writeln!(lib, "pub const {name}: Option<usize> = None;")?,
+            }
+        }
+        writeln!(lib)?;
 
         let wide = self
             .sdk
@@ -217,16 +315,18 @@ glam.workspace = true
     dead_code
 )]
 
-use ucore::{UObject, Ptr, TArray, TSet, TMap, FString, FName, SyncLazy, impl_uobject_like, impl_process_event_fns};
+use ucore::{UObject, Ptr, TArray, TArrayView, TSet, TMap, FString, FText, FName, TWeakObjectPtr, TSoftObjectPtr, TSoftClassPtr, TLazyObjectPtr, TScriptInterface, FScriptDelegate, FMulticastScriptDelegate, FSparseDelegate, TScriptDelegate, TMulticastScriptDelegate, DelegateSignature, TFieldPath, FField, FProperty, SyncLazy, impl_uobject_like, impl_process_event_fns};
 use std::{ptr::NonNull, mem::zeroed};
-use uproxy::PROCESS_EVENT_INDEX;
+use uproxy::{PROCESS_EVENT_INDEX, FNAME_SIZE};
+
+const _: () = assert!(std::mem::size_of::<FName>() == FNAME_SIZE, "ucore FName layout doesn't match the game");
 
 "#;
 
         let mut lib = BufWriter::new(opts.open(folder.join(format!("{}.rs", pkg.ident)))?);
         lib.write_all(
             PRELUDE
-                .replace('%', &format!("{:#X}", State::get().config.process_event))
+                .replace('%', &format!("{:#X}", self.sdk.config.process_event))
                 .as_bytes(),
         )?;
 
@@ -502,9 +602,16 @@ ucore.workspace = true"#
         }
 
         let funcs = functions.borrow();
+        let (signatures, funcs) = funcs
+            .iter()
+            .partition::<Vec<_>, _>(|f| f.flags.contains(FunctionFlags::Delegate));
+        for signature in signatures.iter() {
+            self.write_signature(w, ident, signature)?;
+        }
+
         if !funcs.is_empty() {
             let (static_fns, nonstatic_fns) = funcs
-                .iter()
+                .into_iter()
                 .partition::<Vec<_>, _>(|f| f.flags.contains(FunctionFlags::Static));
 
             writeln!(
@@ -554,6 +661,39 @@ ucore.workspace = true"#
     //     skip.contains(&ustruct.fqn)
     // }
 
+    /// Parameters of a delegate signature, used by the typed delegate fields.
+    fn write_signature(&self, w: &mut dyn WriteIo, ident: &str, func: &Function) -> Result<()> {
+        let name = format!("{ident}_{}", func.ident);
+        writeln!(w, "#[repr(C)]\npub struct {name} {{")?;
+
+        let mut argd = NameDedup::default();
+        for arg in func.args.iter() {
+            let ty = self
+                .stringify_type(&arg.kind, PointerMode::Ptr)
+                .unwrap_or_else(|| Cow::from("*const ()"));
+            writeln!(w, "    pub {}: {ty},", argd.entry(&arg.name))?;
+        }
+
+        writeln!(
+            w,
+            "}}\n\nunsafe impl DelegateSignature for {name} {{\n    const PROCESS_EVENT_INDEX: usize = PROCESS_EVENT_INDEX;\n}}\n"
+        )?;
+
+        Ok(())
+    }
+
+    /// Name of the struct emitted by `write_signature`.
+    fn signature_ident(&self, fqn: &Fqn) -> Option<String> {
+        let function = self.sdk.lookup_function(fqn)?;
+        let owner = Fqn::from_parts(fqn.parts()[1..].iter().copied());
+
+        Some(format!(
+            "{}_{}",
+            self.sdk.lookup(&owner)?.ptr.ident(),
+            function.ident
+        ))
+    }
+
     fn write_function(
         &self,
         w: &mut dyn WriteIo,
@@ -674,6 +814,7 @@ ucore.workspace = true"#
             PropertyKind::Float64 => "f64".into(),
             PropertyKind::Name => "FName".into(),
             PropertyKind::String => "FString".into(),
+            PropertyKind::Text => "FText".into(),
             PropertyKind::Ptr(inner) => {
                 let object = self.sdk.lookup(inner).unwrap();
                 match mode {
@@ -681,6 +822,25 @@ ucore.workspace = true"#
                     PointerMode::Ptr => format!("Option<Ptr<{}>>", object.ptr.ident()).into(),
                 }
             }
+            PropertyKind::Weak(inner) => self.wrap("TWeakObjectPtr", inner)?,
+            PropertyKind::Soft(inner) => self.wrap("TSoftObjectPtr", inner)?,
+            PropertyKind::SoftClass(inner) => self.wrap("TSoftClassPtr", inner)?,
+            PropertyKind::Lazy(inner) => self.wrap("TLazyObjectPtr", inner)?,
+            PropertyKind::Interface(inner) => self.wrap("TScriptInterface", inner)?,
+            PropertyKind::Delegate(signature) => match self.signature_ident(signature) {
+                Some(signature) => format!("TScriptDelegate<{signature}>").into(),
+                None => "FScriptDelegate".into(),
+            },
+            PropertyKind::MulticastDelegate(signature) => match self.signature_ident(signature) {
+                Some(signature) => format!("TMulticastScriptDelegate<{signature}>").into(),
+                None => "FMulticastScriptDelegate".into(),
+            },
+            PropertyKind::SparseDelegate(_) => "FSparseDelegate".into(),
+            // Only the base property type is available in ucore.
+            PropertyKind::FieldPath(class) if class.ends_with("Property") => {
+                "TFieldPath<FProperty>".into()
+            }
+            PropertyKind::FieldPath(_) => "TFieldPath<FField>".into(),
             PropertyKind::Inline(inner) => {
                 let object = self.sdk.lookup(inner).unwrap();
                 if self.options.glam {
@@ -722,7 +882,11 @@ ucore.workspace = true"#
                 format!("[{}; {:#X}]", self.stringify_type(kind, mode)?, *size).into()
             }
             PropertyKind::Vec(inner) => {
-                format!("TArray<{}>", self.stringify_type(inner, mode)?).into()
+                let inner = self.stringify_type(inner, mode)?;
+                match mode {
+                    PointerMode::Mut => format!("TArray<{inner}>").into(),
+                    PointerMode::Ptr => format!("TArrayView<{inner}>").into(),
+                }
             }
             PropertyKind::Set(inner) => {
                 format!("TSet<{}>", self.stringify_type(inner, mode)?).into()
@@ -733,35 +897,22 @@ ucore.workspace = true"#
                 self.stringify_type(value, mode)?
             )
             .into(),
-            // TODO: stringify text and implement in ucore
-            PropertyKind::Text | PropertyKind::Unknown => return None,
+            PropertyKind::Unknown => return None,
         };
 
         Some(repr)
     }
+
+    /// Pointer type generic over the object, e.g. `TSoftObjectPtr<AActor>`.
+    fn wrap(&self, tmpl: &str, inner: &Fqn) -> Option<Cow<'static, str>> {
+        Some(format!("{tmpl}<{}>", self.sdk.lookup(inner)?.ptr.ident()).into())
+    }
 }
 
 #[derive(Clone, Copy)]
 enum PointerMode {
+    /// Values passed to the game, i.e. input parameters.
     Mut,
+    /// Values owned by the game, i.e. fields and output parameters.
     Ptr,
 }
-
-#[derive(Default)]
-struct NameDedup(HashMap<u64, usize>);
-
-impl NameDedup {
-    fn entry<'n>(&mut self, name: &'n str) -> Cow<'n, str> {
-        let mut hasher = DefaultHasher::new();
-        hasher.write_usize(name.len());
-        hasher.write(name.as_bytes());
-
-        let i = self.0.entry(hasher.finish()).or_insert(0);
-        *i += 1;
-
-        match *i {
-            1 => Cow::Borrowed(name),
-            j => Cow::Owned(format!("{name}_{j}")),
-        }
-    }
-}
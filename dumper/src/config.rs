@@ -1,14 +1,25 @@
-use serde::Deserialize;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Config {
     pub stride: u32,
     pub process_event: u32,
     pub level_actors: Option<u32>,
+    #[serde(default)]
+    pub property_model: PropertyModel,
+    #[serde(default)]
+    pub name_pool: NamePoolKind,
 
     pub offsets: Option<Offsets>,
 
+    #[serde(rename = "FName", default)]
+    pub fname: OfFName,
+    /// Required by [`NamePoolKind::TNameEntryArray`].
+    #[serde(rename = "FNameEntry")]
+    pub fname_entry: Option<OfFNameEntry>,
     #[serde(rename = "FUObjectItem")]
     pub fuobject_item: OfFUObjectItem,
     #[serde(rename = "UObject")]
@@ -19,7 +30,8 @@ pub struct Config {
     pub ustruct: OfUStruct,
     #[serde(rename = "UEnum")]
     pub uenum: OfUEnum,
-    #[serde(rename = "FField")]
+    // Not used by the UProperty model.
+    #[serde(rename = "FField", default)]
     pub ffield: OfFField,
     #[serde(rename = "FProperty")]
     pub fproperty: OfFProperty,
@@ -27,7 +39,28 @@ pub struct Config {
     pub ufunction: OfUFunction,
 }
 
-#[derive(Debug, Deserialize)]
+/// How reflected properties are stored by the engine.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PropertyModel {
+    /// UE4.25+, properties are `FProperty` fields linked through `UStruct::ChildProperties`.
+    #[default]
+    FField,
+    /// Pre UE4.25, properties are `UProperty` objects linked through `UStruct::Children`
+    /// together with functions. `FProperty` offsets describe `UProperty` in this case.
+    UProperty,
+}
+
+/// Layout of the global name table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NamePoolKind {
+    /// UE4.23+ block based `FNamePool`, `Names` offset points to the pool itself.
+    #[default]
+    FNamePool,
+    /// Pre UE4.23 chunked `TNameEntryArray`, `Names` offset points to `GNames` pointer.
+    TNameEntryArray,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Offsets {
     pub names: Option<usize>,
@@ -42,20 +75,88 @@ impl Default for Config {
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl Config {
+    /// Serializes config in the same format as files in `configs` folder.
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct OfFName {
+    pub size: usize,
+    /// ComparisonIndex
+    pub index: usize,
+    /// Only present in `WITH_CASE_PRESERVING_NAME` builds.
+    pub display_index: Option<usize>,
+    /// Missing when numbers are outlined into name entries.
+    pub number: Option<usize>,
+    /// Offset of `FNameEntryHeader` in `FNamePool` entries, see [`OfFName::header_offset`].
+    pub entry_header: Option<usize>,
+    /// Offset of `Blocks` in `FNamePool`.
+    pub pool_blocks: usize,
+    /// Positions of `bIsWide` and `Len` in `FNameEntryHeader`.
+    pub wide_bit: u32,
+    pub len_bit: u32,
+}
+
+impl Default for OfFName {
+    fn default() -> Self {
+        DEFAULT.fname
+    }
+}
+
+impl OfFName {
+    #[inline]
+    pub fn outline_number(&self) -> bool {
+        self.number.is_none()
+    }
+
+    /// `ComparisonId` goes before the header of the entry in case preserving builds.
+    #[inline]
+    pub fn header_offset(&self) -> usize {
+        let comparison_id = self.display_index.map_or(0, |_| size_of::<u32>());
+        self.entry_header.unwrap_or(comparison_id)
+    }
+
+    /// Size of `TPair<FName, int64>`.
+    #[inline]
+    pub fn pair_size(&self) -> usize {
+        self.size.next_multiple_of(size_of::<i64>()) + size_of::<i64>()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
+pub struct OfFNameEntry {
+    pub index: usize,
+    pub name: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
 pub struct OfFUObjectItem {
     pub size: usize,
+    /// Offsets of `Flags` and `SerialNumber`, only used by the generated SDK.
+    pub flags: usize,
+    pub serial: usize,
+}
+
+impl Default for OfFUObjectItem {
+    fn default() -> Self {
+        DEFAULT.fuobject_item
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfUFunction {
     pub flags: usize,
     pub func: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfUObject {
     pub index: usize,
@@ -64,28 +165,30 @@ pub struct OfUObject {
     pub outer: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfUField {
     pub next: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfUStruct {
     pub super_struct: usize,
-    // pub children: usize,
+    pub children: Option<usize>,
+    // Not used by the UProperty model.
+    #[serde(default)]
     pub children_props: usize,
     pub props_size: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfUEnum {
     pub names: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfFField {
     pub class: usize,
@@ -93,7 +196,7 @@ pub struct OfFField {
     pub name: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct OfFProperty {
     pub array_dim: usize,
@@ -107,9 +210,26 @@ const DEFAULT: Config = Config {
     stride: 2,
     process_event: 0x4D,
     level_actors: None,
+    property_model: PropertyModel::FField,
+    name_pool: NamePoolKind::FNamePool,
     offsets: None,
 
-    fuobject_item: OfFUObjectItem { size: 0x18 },
+    fname: OfFName {
+        size: 0x8,
+        index: 0x0,
+        display_index: None,
+        number: Some(0x4),
+        entry_header: None,
+        pool_blocks: 0x10,
+        wide_bit: 0,
+        len_bit: 6,
+    },
+    fname_entry: None,
+    fuobject_item: OfFUObjectItem {
+        size: 0x18,
+        flags: 0x8,
+        serial: 0x10,
+    },
     uobject: OfUObject {
         index: 0xC,
         class: 0x10,
@@ -119,7 +239,7 @@ const DEFAULT: Config = Config {
     ufield: OfUField { next: 0x28 },
     ustruct: OfUStruct {
         super_struct: 0x40,
-        // children: 0x48,
+        children: Some(0x48),
         children_props: 0x50,
         props_size: 0x58,
     },
@@ -0,0 +1,555 @@
+use crate::{
+    engine::PropertyFlags,
+    sdk::{Enum, Field, Function, Object, ObjectInfo, Sdk, Struct},
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
+
+/// Differences between two dumps of the same game.
+#[derive(Debug, Default, Serialize)]
+pub struct SdkDiff {
+    pub added_packages: Vec<String>,
+    pub removed_packages: Vec<String>,
+    pub added_objects: Vec<String>,
+    pub removed_objects: Vec<String>,
+    pub changed_objects: Vec<ObjectDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectDiff {
+    pub fqn: String,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Kind {
+        old: String,
+        new: String,
+    },
+    Package {
+        old: String,
+        new: String,
+    },
+    Parent {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Size {
+        old: usize,
+        new: usize,
+    },
+    AddedField {
+        name: String,
+        offset: usize,
+        kind: String,
+    },
+    RemovedField {
+        name: String,
+    },
+    FieldOffset {
+        name: String,
+        old: usize,
+        new: usize,
+    },
+    FieldSize {
+        name: String,
+        old: usize,
+        new: usize,
+    },
+    FieldType {
+        name: String,
+        old: String,
+        new: String,
+    },
+    AddedVariant {
+        name: String,
+        value: i64,
+    },
+    RemovedVariant {
+        name: String,
+    },
+    VariantValue {
+        name: String,
+        old: i64,
+        new: i64,
+    },
+    AddedFunction {
+        name: String,
+    },
+    RemovedFunction {
+        name: String,
+    },
+    FunctionSignature {
+        name: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl SdkDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_packages.is_empty()
+            && self.removed_packages.is_empty()
+            && self.added_objects.is_empty()
+            && self.removed_objects.is_empty()
+            && self.changed_objects.is_empty()
+    }
+}
+
+/// Compares objects by their [`ucore::Fqn`], fields and functions by their names.
+pub fn diff_sdk(old: &Sdk, new: &Sdk) -> SdkDiff {
+    let mut diff = SdkDiff::default();
+
+    let packages = |sdk: &Sdk| {
+        sdk.packages
+            .node_weights()
+            .map(|p| p.ident.to_string())
+            .collect::<BTreeSet<_>>()
+    };
+    let (old_packages, new_packages) = (packages(old), packages(new));
+    diff.added_packages = new_packages.difference(&old_packages).cloned().collect();
+    diff.removed_packages = old_packages.difference(&new_packages).cloned().collect();
+
+    let (old_objects, new_objects) = (objects(old), objects(new));
+
+    for (fqn, new_info) in new_objects.iter() {
+        let Some(old_info) = old_objects.get(fqn) else {
+            diff.added_objects.push(fqn.clone());
+            continue;
+        };
+
+        let mut changes = vec![];
+
+        let (old_pkg, new_pkg) = (
+            &old.packages[old_info.package].ident,
+            &new.packages[new_info.package].ident,
+        );
+        if old_pkg != new_pkg {
+            changes.push(Change::Package {
+                old: old_pkg.to_string(),
+                new: new_pkg.to_string(),
+            });
+        }
+
+        let (old_kind, new_kind) = (object_kind(&old_info.ptr), object_kind(&new_info.ptr));
+        if old_kind != new_kind {
+            changes.push(Change::Kind {
+                old: old_kind.into(),
+                new: new_kind.into(),
+            });
+        }
+
+        // Class and struct share the layout, so their members are still compared.
+        match (&*old_info.ptr, &*new_info.ptr) {
+            (Object::Enum(old), Object::Enum(new)) => diff_enum(old, new, &mut changes),
+            (
+                Object::Class(old) | Object::Struct(old),
+                Object::Class(new) | Object::Struct(new),
+            ) => diff_struct(old, new, &mut changes),
+            _ => {}
+        }
+
+        if !changes.is_empty() {
+            diff.changed_objects.push(ObjectDiff {
+                fqn: fqn.clone(),
+                changes,
+            });
+        }
+    }
+
+    diff.removed_objects = old_objects
+        .keys()
+        .filter(|fqn| !new_objects.contains_key(*fqn))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+fn objects(sdk: &Sdk) -> BTreeMap<String, &ObjectInfo> {
+    sdk.owned
+        .iter()
+        .map(|(fqn, info)| (fqn.to_string(), info))
+        .collect()
+}
+
+fn object_kind(object: &Object) -> &'static str {
+    match object {
+        Object::Enum(_) => "enum",
+        Object::Class(_) => "class",
+        Object::Struct(_) => "struct",
+    }
+}
+
+fn diff_enum(old: &Enum, new: &Enum, changes: &mut Vec<Change>) {
+    if old.layout.size != new.layout.size {
+        changes.push(Change::Size {
+            old: old.layout.size,
+            new: new.layout.size,
+        });
+    }
+
+    let variants = |e: &Enum| e.variants.iter().cloned().collect::<BTreeMap<_, _>>();
+    let (old_variants, new_variants) = (variants(old), variants(new));
+
+    for (name, value) in new_variants.iter() {
+        match old_variants.get(name) {
+            None => changes.push(Change::AddedVariant {
+                name: name.clone(),
+                value: *value,
+            }),
+            Some(old) if old != value => changes.push(Change::VariantValue {
+                name: name.clone(),
+                old: *old,
+                new: *value,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for name in old_variants
+        .keys()
+        .filter(|n| !new_variants.contains_key(*n))
+    {
+        changes.push(Change::RemovedVariant { name: name.clone() });
+    }
+}
+
+struct FieldInfo {
+    offset: usize,
+    size: usize,
+    kind: String,
+}
+
+fn fields(ustruct: &Struct) -> BTreeMap<String, FieldInfo> {
+    let mut out = BTreeMap::new();
+    for field in ustruct.fields.iter() {
+        match field {
+            Field::Property {
+                name,
+                kind,
+                options,
+            } => {
+                let info = FieldInfo {
+                    offset: options.offset,
+                    size: options.total_size(),
+                    kind: kind.to_string(),
+                };
+                out.insert(name.clone(), info);
+            }
+            Field::Bitfields(group) => {
+                for bit in group.items.iter() {
+                    let info = FieldInfo {
+                        offset: group.offset,
+                        size: 1,
+                        kind: format!("bool:{} (bit {})", bit.len, bit.offset),
+                    };
+                    out.insert(bit.name.clone(), info);
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn signature(func: &Function) -> String {
+    let args = func
+        .args
+        .iter()
+        .map(|a| {
+            let out = if a.flags.contains(PropertyFlags::OutParm) {
+                "out "
+            } else {
+                ""
+            };
+            format!("{out}{}: {}", a.name, a.kind)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("({args})")
+}
+
+fn diff_struct(old: &Struct, new: &Struct, changes: &mut Vec<Change>) {
+    if old.parent != new.parent {
+        changes.push(Change::Parent {
+            old: old.parent.map(|p| p.to_string()),
+            new: new.parent.map(|p| p.to_string()),
+        });
+    }
+
+    if old.layout.size != new.layout.size {
+        changes.push(Change::Size {
+            old: old.layout.size,
+            new: new.layout.size,
+        });
+    }
+
+    let (old_fields, new_fields) = (fields(old), fields(new));
+    for (name, field) in new_fields.iter() {
+        let Some(prev) = old_fields.get(name) else {
+            changes.push(Change::AddedField {
+                name: name.clone(),
+                offset: field.offset,
+                kind: field.kind.clone(),
+            });
+            continue;
+        };
+
+        if prev.offset != field.offset {
+            changes.push(Change::FieldOffset {
+                name: name.clone(),
+                old: prev.offset,
+                new: field.offset,
+            });
+        }
+
+        if prev.size != field.size {
+            changes.push(Change::FieldSize {
+                name: name.clone(),
+                old: prev.size,
+                new: field.size,
+            });
+        }
+
+        if prev.kind != field.kind {
+            changes.push(Change::FieldType {
+                name: name.clone(),
+                old: prev.kind.clone(),
+                new: field.kind.clone(),
+            });
+        }
+    }
+
+    for name in old_fields.keys().filter(|n| !new_fields.contains_key(*n)) {
+        changes.push(Change::RemovedField { name: name.clone() });
+    }
+
+    let functions = |s: &Struct| {
+        s.functions
+            .borrow()
+            .iter()
+            .map(|f| (f.ident.clone(), signature(f)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (old_functions, new_functions) = (functions(old), functions(new));
+    for (name, sig) in new_functions.iter() {
+        match old_functions.get(name) {
+            None => changes.push(Change::AddedFunction { name: name.clone() }),
+            Some(old) if old != sig => changes.push(Change::FunctionSignature {
+                name: name.clone(),
+                old: old.clone(),
+                new: sig.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for name in old_functions
+        .keys()
+        .filter(|n| !new_functions.contains_key(*n))
+    {
+        changes.push(Change::RemovedFunction { name: name.clone() });
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opt = |v: &Option<String>| v.clone().unwrap_or_else(|| "none".into());
+
+        match self {
+            Change::Kind { old, new } => write!(f, "kind {old} -> {new}"),
+            Change::Package { old, new } => write!(f, "package {old} -> {new}"),
+            Change::Parent { old, new } => write!(f, "parent {} -> {}", opt(old), opt(new)),
+            Change::Size { old, new } => write!(f, "size {old:#X} -> {new:#X}"),
+            Change::AddedField { name, offset, kind } => {
+                write!(f, "+ field {name}: {kind} at {offset:#X}")
+            }
+            Change::RemovedField { name } => write!(f, "- field {name}"),
+            Change::FieldOffset { name, old, new } => {
+                write!(f, "field {name} offset {old:#X} -> {new:#X}")
+            }
+            Change::FieldSize { name, old, new } => {
+                write!(f, "field {name} size {old:#X} -> {new:#X}")
+            }
+            Change::FieldType { name, old, new } => write!(f, "field {name} type {old} -> {new}"),
+            Change::AddedVariant { name, value } => write!(f, "+ variant {name} = {value}"),
+            Change::RemovedVariant { name } => write!(f, "- variant {name}"),
+            Change::VariantValue { name, old, new } => {
+                write!(f, "variant {name} value {old} -> {new}")
+            }
+            Change::AddedFunction { name } => write!(f, "+ function {name}"),
+            Change::RemovedFunction { name } => write!(f, "- function {name}"),
+            Change::FunctionSignature { name, old, new } => {
+                write!(f, "function {name} {old} -> {new}")
+            }
+        }
+    }
+}
+
+impl Display for SdkDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        for pkg in self.added_packages.iter() {
+            writeln!(f, "+ package {pkg}")?;
+        }
+        for pkg in self.removed_packages.iter() {
+            writeln!(f, "- package {pkg}")?;
+        }
+        for fqn in self.added_objects.iter() {
+            writeln!(f, "+ {fqn}")?;
+        }
+        for fqn in self.removed_objects.iter() {
+            writeln!(f, "- {fqn}")?;
+        }
+        for object in self.changed_objects.iter() {
+            writeln!(f, "~ {}", object.fqn)?;
+            for change in object.changes.iter() {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_sdk, Change};
+    use crate::{
+        sdk::{Enum, Field, FieldOptions, Object, PropertyKind, Sdk, Struct},
+        utils::Layout,
+    };
+    use ucore::fqn;
+
+    fn make_sdk(location: (usize, PropertyKind), variants: &[(&str, i64)]) -> Sdk {
+        make_sdk_with(Object::Struct, location, variants)
+    }
+
+    fn make_sdk_with(
+        object: fn(Struct) -> Object,
+        location: (usize, PropertyKind),
+        variants: &[(&str, i64)],
+    ) -> Sdk {
+        let mut sdk = Sdk::default();
+        let property = |name: &str, offset, elem_size, kind| Field::Property {
+            name: name.into(),
+            kind,
+            options: FieldOptions {
+                offset,
+                elem_size,
+                array_dim: 1,
+            },
+        };
+
+        sdk.add(
+            "Engine",
+            object(Struct {
+                fqn: fqn!(Engine.Actor),
+                index: 0,
+                is_uobject: true,
+                parent: None,
+                ident: "AActor".into(),
+                shrink: None.into(),
+                layout: Layout {
+                    size: 0x40,
+                    align: 8,
+                },
+                fields: vec![
+                    property("Health", 0x28, 4, PropertyKind::Float32),
+                    property("Location", location.0, 0xC, location.1),
+                ],
+                functions: vec![].into(),
+            }),
+        );
+        sdk.add(
+            "Engine",
+            Object::Enum(Enum {
+                fqn: fqn!(Engine.ENetRole),
+                ident: "ENetRole".into(),
+                layout: Layout::same(1),
+                variants: variants.iter().map(|(n, v)| (n.to_string(), *v)).collect(),
+            }),
+        );
+
+        sdk
+    }
+
+    #[test]
+    fn test_diff_sdk() {
+        let old = make_sdk(
+            (0x30, PropertyKind::Inline(fqn!(CoreUObject.Vector))),
+            &[("ROLE_None", 0), ("ROLE_Authority", 3)],
+        );
+        let new = make_sdk(
+            (0x2C, PropertyKind::Inline(fqn!(CoreUObject.Vector3f))),
+            &[("ROLE_None", 0), ("ROLE_Authority", 4), ("ROLE_Proxy", 1)],
+        );
+
+        assert!(diff_sdk(&old, &old).is_empty());
+
+        let diff = diff_sdk(&old, &new);
+        assert!(diff.added_objects.is_empty() && diff.removed_packages.is_empty());
+
+        let actor = &diff.changed_objects[0];
+        assert_eq!(actor.fqn, "Engine.Actor");
+        assert_eq!(
+            actor.changes,
+            [
+                Change::FieldOffset {
+                    name: "Location".into(),
+                    old: 0x30,
+                    new: 0x2C
+                },
+                Change::FieldType {
+                    name: "Location".into(),
+                    old: "CoreUObject.Vector".into(),
+                    new: "CoreUObject.Vector3f".into()
+                },
+            ]
+        );
+
+        let role = &diff.changed_objects[1];
+        assert_eq!(role.changes.len(), 2);
+        assert!(role.changes.contains(&Change::AddedVariant {
+            name: "ROLE_Proxy".into(),
+            value: 1
+        }));
+
+        let text = diff.to_string();
+        assert!(text.contains("~ Engine.Actor\n    field Location offset 0x30 -> 0x2C\n"));
+        assert!(text.contains("    variant ROLE_Authority value 3 -> 4\n"));
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            json["changed_objects"][0]["changes"][0]["change"],
+            "field_offset"
+        );
+    }
+
+    #[test]
+    fn test_diff_kind() {
+        let location = (0x30, PropertyKind::Inline(fqn!(CoreUObject.Vector)));
+        let old = make_sdk_with(Object::Struct, location.clone(), &[]);
+        let new = make_sdk_with(Object::Class, location, &[]);
+
+        let diff = diff_sdk(&old, &new);
+        assert_eq!(diff.changed_objects.len(), 1);
+        assert_eq!(
+            diff.changed_objects[0].changes,
+            [Change::Kind {
+                old: "struct".into(),
+                new: "class".into()
+            }]
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    config::NamePoolKind,
+    names::{FNamePoolReader, NameReader},
+    Config, External,
+};
+use anyhow::{bail, ensure, Result};
+use log::{debug, info};
+use std::mem::size_of;
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x40;
+const IMAGE_SCN_CNT_UNINITIALIZED_DATA: u32 = 0x80;
+
+const PAGE_SIZE: usize = 0x1000;
+const NUM_ELEMENTS_PER_CHUNK: u32 = 64 * 1024;
+const MAX_NAME_BLOCKS: u32 = 1 << 13;
+const FNAME_BLOCK_OFFSETS: u32 = 1 << 16;
+
+// All patterns start with a 7 byte RIP relative instruction referencing the target.
+const NAMES_PATTERNS: &[&str] = &[
+    // lea rcx, NamePoolData; call FNamePool::FNamePool; mov byte ptr bNamePoolInitialized, 1
+    "48 8D 0D ? ? ? ? E8 ? ? ? ? C6 05 ? ? ? ? 01",
+    // lea rax, NamePoolData; jmp ?; lea rcx, NamePoolData; call FNamePool::FNamePool
+    "48 8D 05 ? ? ? ? EB ? 48 8D 0D ? ? ? ? E8",
+];
+
+const OBJECTS_PATTERNS: &[&str] = &[
+    // mov rax, GUObjectArray.ObjObjects.Objects; mov rcx, [rax + rcx * 8]; lea rax, [rcx + rdx * 8]
+    "48 8B 05 ? ? ? ? 48 8B 0C C8 48 8D 04 D1",
+    "48 8B 05 ? ? ? ? 48 8B 0C C8 4C 8D 04 D1",
+];
+
+/// Offsets of the global engine structures relative to the module base.
+#[derive(Debug, Clone, Copy)]
+pub struct DiscoveredOffsets {
+    /// FNamePool
+    pub names: usize,
+    /// TUObjectArray
+    pub objects: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Section {
+    pub name: String,
+    pub address: usize,
+    pub size: usize,
+    pub characteristics: u32,
+}
+
+impl Section {
+    pub fn is_code(&self) -> bool {
+        self.characteristics & IMAGE_SCN_CNT_CODE != 0
+    }
+
+    fn is_data(&self) -> bool {
+        !self.is_code()
+            && self.characteristics
+                & (IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_CNT_UNINITIALIZED_DATA)
+                != 0
+    }
+}
+
+/// Locates FNamePool and TUObjectArray in the module loaded at `base`.
+/// Candidates are first taken from known code signatures and then from a brute force
+/// scan of data sections, every candidate is validated before being accepted.
+pub fn discover_offsets(
+    proc: &dyn External,
+    base: usize,
+    config: &Config,
+) -> Result<DiscoveredOffsets> {
+    ensure!(
+        config.name_pool == NamePoolKind::FNamePool,
+        "Offset discovery only supports FNamePool"
+    );
+
+    let sections = module_sections(proc, base)?;
+    for section in sections.iter() {
+        debug!(
+            "Section {} at {:#X}, size = {:#X}",
+            section.name, section.address, section.size
+        );
+    }
+
+    let code = sections
+        .iter()
+        .filter(|s| s.is_code())
+        .map(|s| (s.address, read_section(proc, s)))
+        .collect::<Vec<_>>();
+    let data = sections
+        .iter()
+        .filter(|s| s.is_data())
+        .map(|s| (s.address, read_section(proc, s)))
+        .collect::<Vec<_>>();
+
+    let names = find_global(&code, &data, NAMES_PATTERNS, |address, local| {
+        is_name_pool(proc, address, local, config)
+    });
+    let Some(names) = names else {
+        bail!("Failed to find FNamePool")
+    };
+    info!("Found FNamePool at {:#X}", names - base);
+
+    let objects = find_global(&code, &data, OBJECTS_PATTERNS, |address, local| {
+        is_object_array(proc, address, local, names, config)
+    });
+    let Some(objects) = objects else {
+        bail!("Failed to find TUObjectArray")
+    };
+    info!("Found TUObjectArray at {:#X}", objects - base);
+
+    Ok(DiscoveredOffsets {
+        names: names - base,
+        objects: objects - base,
+    })
+}
+
+pub(crate) fn module_sections(proc: &dyn External, base: usize) -> Result<Vec<Section>> {
+    ensure!(proc.read::<[u8; 2]>(base)? == *b"MZ", "Invalid DOS header");
+    let nt = base + proc.read::<u32>(base + 0x3C)? as usize;
+    ensure!(
+        proc.read::<[u8; 4]>(nt)? == *b"PE\0\0",
+        "Invalid NT headers"
+    );
+
+    let num_sections = proc.read::<u16>(nt + 0x6)? as usize;
+    let optional_size = proc.read::<u16>(nt + 0x14)? as usize;
+    let headers = nt + 0x18 + optional_size;
+
+    (0..num_sections)
+        .map(|i| {
+            let header = headers + i * 0x28;
+            let name = proc.read::<[u8; 8]>(header)?;
+            let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+
+            Ok(Section {
+                name: String::from_utf8_lossy(&name[..len]).into_owned(),
+                size: proc.read::<u32>(header + 0x8)? as usize,
+                address: base + proc.read::<u32>(header + 0xC)? as usize,
+                characteristics: proc.read::<u32>(header + 0x24)?,
+            })
+        })
+        .collect()
+}
+
+// Pages that can't be read are left zeroed.
+fn read_section(proc: &dyn External, section: &Section) -> Vec<u8> {
+    let mut data = vec![0; section.size];
+    for (i, page) in data.chunks_mut(PAGE_SIZE).enumerate() {
+        _ = proc.read_buf(section.address + i * PAGE_SIZE, page);
+    }
+
+    data
+}
+
+fn find_global(
+    code: &[(usize, Vec<u8>)],
+    data: &[(usize, Vec<u8>)],
+    patterns: &[&str],
+    mut validate: impl FnMut(usize, Option<&[u8]>) -> bool,
+) -> Option<usize> {
+    let patterns = patterns
+        .iter()
+        .map(|p| parse_pattern(p))
+        .collect::<Vec<_>>();
+
+    for (address, bytes) in code.iter() {
+        for pattern in patterns.iter() {
+            for offset in find_pattern(bytes, pattern) {
+                let disp = i32::from_le_bytes(bytes[offset + 3..offset + 7].try_into().unwrap());
+                let target = (address + offset + 7).wrapping_add_signed(disp as isize);
+
+                debug!("Pattern match at {:#X} -> {target:#X}", address + offset);
+                if validate(target, None) {
+                    return Some(target);
+                }
+            }
+        }
+    }
+
+    debug!("Signatures failed, scanning data sections");
+    data.iter().find_map(|(address, bytes)| {
+        (0..bytes.len())
+            .step_by(size_of::<usize>())
+            .find(|offset| validate(address + offset, Some(&bytes[*offset..])))
+            .map(|offset| address + offset)
+    })
+}
+
+fn parse_pattern(pattern: &str) -> Vec<Option<u8>> {
+    pattern
+        .split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect()
+}
+
+fn find_pattern<'a>(
+    bytes: &'a [u8],
+    pattern: &'a [Option<u8>],
+) -> impl Iterator<Item = usize> + 'a {
+    bytes
+        .windows(pattern.len())
+        .enumerate()
+        .filter_map(move |(i, w)| {
+            w.iter()
+                .zip(pattern)
+                .all(|(b, p)| p.map(|p| p == *b).unwrap_or(true))
+                .then_some(i)
+        })
+}
+
+fn read_local<T: Copy>(local: &[u8], offset: usize) -> Option<T> {
+    let bytes = local.get(offset..offset + size_of::<T>())?;
+    Some(unsafe { bytes.as_ptr().cast::<T>().read_unaligned() })
+}
+
+// Cheap checks on the local copy of the section are done first, because brute force
+// scanning would otherwise issue reads for every pointer-sized slot.
+fn is_name_pool(
+    proc: &dyn External,
+    address: usize,
+    local: Option<&[u8]>,
+    config: &Config,
+) -> bool {
+    let header = match local {
+        Some(local) => read_local::<[u32; 6]>(local, 0),
+        None => proc.read::<[u32; 6]>(address).ok(),
+    };
+    let Some([_, _, current_block, cursor, first, first_high]) = header else {
+        return false;
+    };
+
+    let first = first as usize | (first_high as usize) << 32;
+    if current_block >= MAX_NAME_BLOCKS
+        || cursor == 0
+        || cursor > config.stride * FNAME_BLOCK_OFFSETS
+        || first == 0
+        || !first.is_multiple_of(size_of::<usize>())
+    {
+        return false;
+    }
+
+    FNamePoolReader::new(config)
+        .read(proc, address, 0)
+        .is_ok_and(|name| name == "None")
+}
+
+fn is_object_array(
+    proc: &dyn External,
+    address: usize,
+    local: Option<&[u8]>,
+    names: usize,
+    config: &Config,
+) -> bool {
+    let header = match local {
+        Some(local) => read_local::<[u32; 8]>(local, 0),
+        None => proc.read::<[u32; 8]>(address).ok(),
+    };
+    let Some([chunks, chunks_high, _, _, max_elems, num_elems, max_chunks, num_chunks]) = header
+    else {
+        return false;
+    };
+
+    let chunks = chunks as usize | (chunks_high as usize) << 32;
+    if chunks == 0
+        || !chunks.is_multiple_of(size_of::<usize>())
+        || num_elems < 2
+        || num_elems > max_elems
+        || num_chunks == 0
+        || num_chunks > max_chunks
+        || num_chunks != num_elems.div_ceil(NUM_ELEMENTS_PER_CHUNK)
+    {
+        return false;
+    }
+
+    let name_of = |idx: usize| -> Result<String> {
+        let chunk = proc.read::<usize>(chunks)?;
+        let object = proc.read::<usize>(chunk + idx * config.fuobject_item.size)?;
+        let id = proc.read::<u32>(object + config.uobject.name + config.fname.index)?;
+
+        FNamePoolReader::new(config).read(proc, names, id)
+    };
+
+    name_of(0).is_ok_and(|n| n == "/Script/CoreUObject") && name_of(1).is_ok_and(|n| n == "Object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::discover_offsets;
+    use crate::{fixture::Image, Config};
+
+    #[test]
+    fn test_discover_offsets() {
+        let mut image = Image::new(Config::default());
+
+        // lea rcx, NamePoolData; call ?; mov byte ptr ?, 1
+        let at = image.base() + image.text_offset() + 0x10;
+        let disp = (image.base() + image.names_offset()) as isize - (at + 7) as isize;
+        image.write_bytes(at, &[0x48, 0x8D, 0x0D]);
+        image.write(at + 3, disp as i32);
+        image.write_bytes(at + 7, &[0xE8, 0, 0, 0, 0, 0xC6, 0x05, 0, 0, 0, 0, 0x01]);
+
+        // TUObjectArray has no signature and must be found by scanning `.data`.
+        let (base, names, objects) = (image.base(), image.names_offset(), image.objects_offset());
+        let config = image.config.clone();
+        let offsets = discover_offsets(&image.finish(), base, &config).unwrap();
+
+        assert_eq!(offsets.names, names);
+        assert_eq!(offsets.objects, objects);
+    }
+}
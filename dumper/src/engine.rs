@@ -1,23 +1,26 @@
-use crate::{utils::strip_package_name, Config, State};
-use anyhow::{ensure, Result};
+use crate::{
+    config::{OfFField, PropertyModel},
+    utils::strip_package_name,
+    Config, Dumper,
+};
+use anyhow::{ensure, Context, Result};
 use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
 use std::{iter::successors, mem::size_of};
-use ucore::Fqn;
+use ucore::{fqn, Fqn};
 
 macro_rules! mkfn {
     ($field:ident $kind:ident, = $offset:expr) => {
         #[allow(clippy::redundant_closure_call)]
-        pub fn $field(&self) -> Result<$kind> {
-            State::get()
-                .external
-                .read(self.0 + ($offset)(&State::get().config))
+        pub fn $field(&self, dumper: &Dumper) -> Result<$kind> {
+            dumper.read(self.0 + ($offset)(&dumper.config))
         }
     };
 
     ($field:ident $kind:ident, @ $offset:expr) => {
         #[allow(clippy::redundant_closure_call)]
-        pub fn $field(&self) -> $kind {
-            $kind(self.0 + ($offset)(&State::get().config))
+        pub fn $field(&self, dumper: &Dumper) -> $kind {
+            $kind(self.0 + ($offset)(&dumper.config))
         }
     };
 }
@@ -76,6 +79,18 @@ macro_rules! mkptr {
 
 type C = Config;
 
+// UProperties are UObjects, so they are laid out as any other UField.
+fn field_offsets(c: &C) -> OfFField {
+    match c.property_model {
+        PropertyModel::FField => c.ffield.clone(),
+        PropertyModel::UProperty => OfFField {
+            class: c.uobject.class,
+            next: c.ufield.next,
+            name: c.uobject.name,
+        },
+    }
+}
+
 mkptr! {
     UObjectPtr {
         index u32: = |c: &C| c.uobject.index,
@@ -98,16 +113,18 @@ mkptr! {
         func usize: = |c: &C| c.ufunction.func,
     }
     FFieldPtr {
-        class FFieldClassPtr: = |c: &C| c.ffield.class,
-        name FNamePtr: @ |c: &C| c.ffield.name,
-        next FFieldPtr: = |c: &C| c.ffield.next
+        class FFieldClassPtr: = |c: &C| field_offsets(c).class,
+        name FNamePtr: @ |c: &C| field_offsets(c).name,
+        next FFieldPtr: = |c: &C| field_offsets(c).next
     }
     FFieldClassPtr {
-        name FNamePtr: @ |_| 0
+        name FNamePtr: @ |c: &C| match c.property_model {
+            PropertyModel::FField => 0,
+            PropertyModel::UProperty => c.uobject.name,
+        }
     }
     UStructPtr {
         super_struct UStructPtr: = |c: &C| c.ustruct.super_struct,
-        // children UFieldPtr: = |c: &C| c.ustruct.children,
         children_props FFieldPtr: = |c: &C| c.ustruct.children_props,
         props_size u32: = |c: &C| c.ustruct.props_size,
         min_align u32: = |c: &C| c.ustruct.props_size + size_of::<u32>(),
@@ -117,14 +134,60 @@ mkptr! {
     FNamePtr {}
 }
 
+impl UStructPtr {
+    pub fn children(&self, dumper: &Dumper) -> Result<UObjectPtr> {
+        let offset = dumper
+            .config
+            .ustruct
+            .children
+            .context("UStruct.Children offset is required")?;
+        dumper.read(self.0 + offset)
+    }
+
+    /// Properties declared by the struct itself, not including inherited ones.
+    pub fn properties<'d>(
+        &self,
+        dumper: &'d Dumper,
+    ) -> Result<Box<dyn Iterator<Item = FFieldPtr> + 'd>> {
+        let next = |field: &FFieldPtr| field.next(dumper).unwrap().non_null();
+
+        Ok(match dumper.config.property_model {
+            PropertyModel::FField => {
+                Box::new(successors(self.children_props(dumper)?.non_null(), next))
+            }
+            // Children also contain functions.
+            PropertyModel::UProperty => Box::new(
+                successors(self.children(dumper)?.cast::<FFieldPtr>().non_null(), next).filter(
+                    |field| {
+                        field
+                            .cast::<UObjectPtr>()
+                            .is_a(dumper, fqn!(CoreUObject.Property))
+                            .unwrap()
+                    },
+                ),
+            ),
+        })
+    }
+}
+
 impl UEnumPtr {
-    pub fn names(&self) -> Result<TArray> {
-        let State {
-            external: proc,
-            config: offsets,
-            ..
-        } = State::get();
-        proc.read::<TArray>(self.0 + offsets.uenum.names)
+    pub fn names(&self, dumper: &Dumper) -> Result<TArray> {
+        dumper.read::<TArray>(self.0 + dumper.config.uenum.names)
+    }
+
+    /// Name ids and values of the enumerators, `TPair<FName, int64>` layout depends on `FName`.
+    pub fn variants(&self, dumper: &Dumper) -> Result<Vec<(u32, i64)>> {
+        let names = self.names(dumper)?;
+        let fname = &dumper.config.fname;
+        let value = fname.pair_size() - size_of::<i64>();
+
+        (0..names.len as usize)
+            .map(|i| {
+                let pair = names.ptr + i * fname.pair_size();
+                let id = FNamePtr(pair).read(dumper)?;
+                Ok((id, dumper.read::<i64>(pair + value)?))
+            })
+            .collect()
     }
 }
 
@@ -144,7 +207,8 @@ pub struct FunctionVars {
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(transparent)]
     pub struct FunctionFlags : u32 {
         const Final= 0x00000001;
         const RequiredAPI = 0x00000002;
@@ -178,7 +242,8 @@ bitflags! {
         const NetValidate = 0x80000000;
     }
 
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    #[serde(transparent)]
     pub struct PropertyFlags : u64 {
         const Edit = 0x0000000000000001;
         const ConstParm = 0x0000000000000002;
@@ -239,48 +304,42 @@ pub struct TArray {
     pub len: u32,
 }
 
-impl TArray {
-    pub fn iter<T>(&self) -> impl Iterator<Item = Result<T>> + '_ {
-        (0..self.len as usize).map(|i| {
-            State::get()
-                .external
-                .read::<T>(self.ptr + i * size_of::<T>())
-                .map_err(Into::into)
-        })
-    }
-}
-
 impl FNamePtr {
-    pub fn read(&self) -> Result<u32> {
-        State::get().external.read(self.0)
+    /// Reads comparison index of the name.
+    pub fn read(&self, dumper: &Dumper) -> Result<u32> {
+        dumper.read(self.0 + dumper.config.fname.index)
     }
 
-    pub(crate) fn get(&self) -> Result<&'static str> {
-        State::get().get_name(self.read()?)
+    pub(crate) fn get(&self, dumper: &Dumper) -> Result<&'static str> {
+        dumper.get_name(self.read(dumper)?)
     }
 }
 
 impl UObjectPtr {
-    pub(crate) fn fqn(&self) -> Result<Fqn> {
-        let outer = self.outer()?;
+    pub(crate) fn fqn(&self, dumper: &Dumper) -> Result<Fqn> {
+        let outer = self.outer(dumper)?;
         ensure!(!outer.is_null(), "Can't get FQN of a package");
 
-        let ascendants = successors(Some(*self), |obj| obj.outer().unwrap().non_null())
-            .map(|obj| strip_package_name(obj.name().get().unwrap()));
+        let ascendants = successors(Some(*self), |obj| obj.outer(dumper).unwrap().non_null())
+            .map(|obj| strip_package_name(obj.name(dumper).get(dumper).unwrap()));
         Ok(Fqn::from_parts(ascendants))
     }
 
-    pub(crate) fn is_a(&self, fqn: Fqn) -> Result<bool> {
+    pub(crate) fn is_a(&self, dumper: &Dumper, fqn: Fqn) -> Result<bool> {
         let descendant = self
-            .inheritance()?
-            .any(|v| v.cast::<UObjectPtr>().fqn().unwrap() == fqn);
+            .inheritance(dumper)?
+            .any(|v| v.cast::<UObjectPtr>().fqn(dumper).unwrap() == fqn);
 
         Ok(descendant)
     }
 
-    pub(crate) fn inheritance(&self) -> Result<impl Iterator<Item = UStructPtr>> {
-        Ok(successors(Some(self.class()?.cast::<UStructPtr>()), |s| {
-            s.super_struct().unwrap().non_null()
-        }))
+    pub(crate) fn inheritance<'d>(
+        &self,
+        dumper: &'d Dumper,
+    ) -> Result<impl Iterator<Item = UStructPtr> + 'd> {
+        Ok(successors(
+            Some(self.class(dumper)?.cast::<UStructPtr>()),
+            |s| s.super_struct(dumper).unwrap().non_null(),
+        ))
     }
 }
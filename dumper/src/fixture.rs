@@ -0,0 +1,1120 @@
+//! Synthetic in-memory image of an unreal engine game laid out according to a [`Config`].
+
+use crate::{
+    config::{NamePoolKind, PropertyModel},
+    engine::{FunctionFlags, PropertyFlags},
+    Config, Dumper, DumperOptions, Sdk, Snapshot,
+};
+use anyhow::Result;
+use std::{collections::HashMap, mem::size_of};
+
+const BASE: usize = 0x7FF6_0000_0000;
+const MODULE_SIZE: usize = 0x3000;
+const HEAP: usize = 0x2000_0000_0000;
+
+// Module consists of PE headers, `.text` and `.data` sections, 0x1000 bytes each.
+const TEXT_OFFSET: usize = 0x1000;
+const DATA_OFFSET: usize = 0x2000;
+const NAMES_OFFSET: usize = DATA_OFFSET + 0x100;
+const OBJECTS_OFFSET: usize = DATA_OFFSET + 0x200;
+const NAME_BLOCK_SIZE: usize = 0x10000;
+const NAME_ARRAY_CHUNKS: usize = 128;
+const NAME_ARRAY_CHUNK_SIZE: usize = 16384;
+const MAX_OBJECTS: usize = 0x400;
+const OBJECT_SIZE: usize = 0x200;
+
+pub(crate) enum Prop {
+    /// `field_mask` of `0xFF` is a native bool.
+    Bool {
+        byte_mask: u8,
+        field_mask: u8,
+    },
+    Int32,
+    Float32,
+    Name,
+    Str,
+    Text,
+    Object(usize),
+    Weak(usize),
+    Soft(usize),
+    /// Signature function of the delegate.
+    Multicast(usize),
+    /// Name of the targeted field class.
+    FieldPath(&'static str),
+    Struct(usize, usize),
+    Enum(usize, usize),
+    Array(Box<Prop>),
+}
+
+impl Prop {
+    fn class(&self) -> &'static str {
+        match self {
+            Prop::Bool { .. } => "BoolProperty",
+            Prop::Int32 => "IntProperty",
+            Prop::Float32 => "FloatProperty",
+            Prop::Name => "NameProperty",
+            Prop::Str => "StrProperty",
+            Prop::Text => "TextProperty",
+            Prop::Object(_) => "ObjectProperty",
+            Prop::Weak(_) => "WeakObjectProperty",
+            Prop::Soft(_) => "SoftObjectProperty",
+            Prop::Multicast(_) => "MulticastInlineDelegateProperty",
+            Prop::FieldPath(_) => "FieldPathProperty",
+            Prop::Struct(..) => "StructProperty",
+            Prop::Enum(..) => "EnumProperty",
+            Prop::Array(_) => "ArrayProperty",
+        }
+    }
+
+    fn elem_size(&self) -> usize {
+        match self {
+            Prop::Bool { .. } => 1,
+            Prop::Int32 | Prop::Float32 => 4,
+            Prop::Name | Prop::Object(_) | Prop::Weak(_) => 8,
+            Prop::Str | Prop::Array(_) | Prop::Multicast(_) => 0x10,
+            Prop::Text => 0x18,
+            Prop::FieldPath(_) => 0x20,
+            Prop::Soft(_) => 0x28,
+            Prop::Struct(_, size) | Prop::Enum(_, size) => *size,
+        }
+    }
+}
+
+/// Well known `CoreUObject` classes, every image starts with them.
+pub(crate) struct Core {
+    pub package: usize,
+    pub object: usize,
+    pub field: usize,
+    pub class: usize,
+    pub script_struct: usize,
+    pub uenum: usize,
+    pub function: usize,
+    pub package_class: usize,
+    pub vector: usize,
+}
+
+pub(crate) struct Image {
+    pub config: Config,
+    pub core: Core,
+    memory: Vec<(usize, Vec<u8>)>,
+    heap: usize,
+    names: HashMap<String, u32>,
+    name_block: usize,
+    name_array: usize,
+    name_cursor: usize,
+    objects: Vec<usize>,
+    object_chunk: usize,
+    field_classes: HashMap<&'static str, usize>,
+    last_fields: HashMap<usize, usize>,
+    last_children: HashMap<usize, usize>,
+}
+
+impl Image {
+    pub fn new(config: Config) -> Self {
+        let mut this = Self {
+            config,
+            memory: vec![(BASE, vec![0; MODULE_SIZE])],
+            heap: HEAP,
+            names: HashMap::new(),
+            name_block: 0,
+            name_array: 0,
+            name_cursor: 0,
+            objects: vec![],
+            object_chunk: 0,
+            field_classes: HashMap::new(),
+            last_fields: HashMap::new(),
+            last_children: HashMap::new(),
+            core: Core {
+                package: 0,
+                object: 0,
+                field: 0,
+                class: 0,
+                script_struct: 0,
+                uenum: 0,
+                function: 0,
+                package_class: 0,
+                vector: 0,
+            },
+        };
+
+        this.write_headers();
+
+        match this.config.name_pool {
+            NamePoolKind::FNamePool => {
+                this.name_block = this.alloc(NAME_BLOCK_SIZE);
+                this.write(
+                    BASE + NAMES_OFFSET + size_of::<usize>() * 2,
+                    this.name_block,
+                );
+            }
+            NamePoolKind::TNameEntryArray => {
+                // 128 chunk pointers followed by NumElements and NumChunks.
+                this.name_array = this.alloc(NAME_ARRAY_CHUNKS * size_of::<usize>() + 8);
+                // Entries are placed right after the only chunk.
+                this.name_block =
+                    this.alloc(NAME_ARRAY_CHUNK_SIZE * size_of::<usize>() + NAME_BLOCK_SIZE);
+                this.write(this.name_array, this.name_block);
+                this.write(
+                    this.name_array + NAME_ARRAY_CHUNKS * size_of::<usize>(),
+                    [0u32, 1],
+                );
+                this.write(BASE + NAMES_OFFSET, this.name_array);
+            }
+        }
+        this.name("None");
+
+        let item_size = this.config.fuobject_item.size;
+        this.object_chunk = this.alloc(MAX_OBJECTS * item_size);
+        let chunks = this.alloc(size_of::<usize>());
+        this.write(chunks, this.object_chunk);
+        this.write(BASE + OBJECTS_OFFSET, chunks);
+        // MaxElements, NumElements, MaxChunks, NumChunks
+        this.write(
+            BASE + OBJECTS_OFFSET + size_of::<usize>() * 2,
+            [MAX_OBJECTS as u32, 0, 1, 1],
+        );
+
+        let package = this.object(0, "/Script/CoreUObject", 0);
+        let object = this.ustruct(0, package, "Object", 0, 0x28, 8);
+        let field = this.ustruct(0, package, "Field", object, 0x30, 8);
+        let ustruct = this.ustruct(0, package, "Struct", field, 0xB0, 8);
+        let class = this.ustruct(0, package, "Class", ustruct, 0x230, 8);
+        for ptr in [object, field, ustruct, class] {
+            this.write(ptr + this.config.uobject.class, class);
+        }
+        this.core.class = class;
+
+        let script_struct = this.class(package, "ScriptStruct", ustruct, 0xC0);
+        let uenum = this.class(package, "Enum", field, 0x60);
+        let function = this.class(package, "Function", ustruct, 0xE0);
+        let package_class = this.class(package, "Package", object, 0x80);
+        this.write(package + this.config.uobject.class, package_class);
+
+        this.core = Core {
+            package,
+            object,
+            field,
+            class,
+            script_struct,
+            uenum,
+            function,
+            package_class,
+            vector: 0,
+        };
+
+        let vector = this.script_struct(package, "Vector", 0xC, 4);
+        for (i, axis) in ["X", "Y", "Z"].into_iter().enumerate() {
+            this.property(vector, axis, Prop::Float32, i * 4);
+        }
+        this.core.vector = vector;
+
+        this
+    }
+
+    fn write_headers(&mut self) {
+        const NT: usize = 0x80;
+        const OPTIONAL_SIZE: u16 = 0xF0;
+
+        self.write_bytes(BASE, b"MZ");
+        self.write(BASE + 0x3C, NT as u32);
+        self.write_bytes(BASE + NT, b"PE\0\0");
+        self.write(BASE + NT + 0x4, 0x8664u16);
+        self.write(BASE + NT + 0x6, 2u16);
+        self.write(BASE + NT + 0x14, OPTIONAL_SIZE);
+        self.write(BASE + NT + 0x18, 0x20Bu16);
+        self.write(BASE + NT + 0x18 + 0x38, MODULE_SIZE as u32);
+
+        let sections = [
+            (b".text\0\0\0", TEXT_OFFSET, 0x6000_0020u32),
+            (b".data\0\0\0", DATA_OFFSET, 0xC000_0040u32),
+        ];
+        let headers = BASE + NT + 0x18 + OPTIONAL_SIZE as usize;
+        for (i, (name, offset, characteristics)) in sections.into_iter().enumerate() {
+            let header = headers + i * 0x28;
+            self.write_bytes(header, name);
+            self.write(header + 0x8, 0x1000u32);
+            self.write(header + 0xC, offset as u32);
+            self.write(header + 0x24, characteristics);
+        }
+    }
+
+    pub fn base(&self) -> usize {
+        BASE
+    }
+
+    pub fn text_offset(&self) -> usize {
+        TEXT_OFFSET
+    }
+
+    pub fn names_offset(&self) -> usize {
+        NAMES_OFFSET
+    }
+
+    pub fn objects_offset(&self) -> usize {
+        OBJECTS_OFFSET
+    }
+
+    pub fn alloc(&mut self, size: usize) -> usize {
+        let address = self.heap;
+        let i = self.memory.partition_point(|(from, _)| *from < address);
+        self.memory.insert(i, (address, vec![0; size]));
+        self.heap += (size + 0xF) & !0xF;
+
+        address
+    }
+
+    pub fn write_bytes(&mut self, address: usize, bytes: &[u8]) {
+        let i = self
+            .memory
+            .partition_point(|(from, _)| *from <= address)
+            .checked_sub(1)
+            .expect("Write outside of the image");
+        let (from, data) = &mut self.memory[i];
+        let offset = address - *from;
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    pub fn write<T: Copy>(&mut self, address: usize, value: T) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts((&value as *const T).cast::<u8>(), size_of::<T>())
+        };
+        self.write_bytes(address, bytes);
+    }
+
+    /// Interns `name` into the name pool, returns its id.
+    pub fn name(&mut self, name: &str) -> u32 {
+        if let Some(id) = self.names.get(name) {
+            return *id;
+        }
+
+        if self.config.name_pool == NamePoolKind::TNameEntryArray {
+            return self.name_entry(name);
+        }
+
+        let stride = self.config.stride as usize;
+        let id = (self.name_cursor / stride) as u32;
+        let header = self.write_entry_id(id);
+        self.write(header, (name.len() as u16) << self.config.fname.len_bit);
+        self.write_bytes(header + size_of::<u16>(), name.as_bytes());
+
+        let size = header - (self.name_block + self.name_cursor) + size_of::<u16>() + name.len();
+        self.name_cursor += size.next_multiple_of(stride);
+        self.write(
+            BASE + NAMES_OFFSET + size_of::<usize>() + size_of::<u32>(),
+            self.name_cursor as u32,
+        );
+
+        self.names.insert(name.into(), id);
+        id
+    }
+
+    /// Writes `ComparisonId` of the entry at the cursor if the layout has one,
+    /// returns address of the entry header.
+    fn write_entry_id(&mut self, id: u32) -> usize {
+        let entry = self.name_block + self.name_cursor;
+        if self.config.fname.display_index.is_some() {
+            self.write(entry, id);
+        }
+
+        entry + self.config.fname.header_offset()
+    }
+
+    /// Writes FName referencing name `id` according to the configured layout.
+    pub fn write_name(&mut self, address: usize, id: u32) {
+        let of = self.config.fname.clone();
+        self.write(address + of.index, id);
+        if let Some(display_index) = of.display_index {
+            self.write(address + display_index, id);
+        }
+    }
+
+    /// Adds outlined entry of `base` name with `number`, returns its id.
+    pub fn numbered_name(&mut self, base: &str, number: u32) -> u32 {
+        assert!(self.config.fname.outline_number());
+        let base = self.name(base);
+
+        let stride = self.config.stride as usize;
+        let id = (self.name_cursor / stride) as u32;
+        let header = self.write_entry_id(id);
+        self.write(header, 0u16);
+        self.write(header + size_of::<u16>(), [base, number + 1]);
+
+        let size = header - (self.name_block + self.name_cursor) + size_of::<u16>() + 8;
+        self.name_cursor += size.next_multiple_of(stride);
+        self.write(
+            BASE + NAMES_OFFSET + size_of::<usize>() + size_of::<u32>(),
+            self.name_cursor as u32,
+        );
+
+        id
+    }
+
+    fn name_entry(&mut self, name: &str) -> u32 {
+        let of = self.config.fname_entry.clone().unwrap();
+        let id = self.names.len();
+
+        let entry = self.name_block + NAME_ARRAY_CHUNK_SIZE * size_of::<usize>() + self.name_cursor;
+        self.name_cursor += (of.name + name.len() + 1).next_multiple_of(size_of::<usize>());
+        self.write(entry + of.index, (id as u32) << 1);
+        self.write_bytes(entry + of.name, name.as_bytes());
+        self.write(self.name_block + id * size_of::<usize>(), entry);
+
+        self.write(
+            self.name_array + NAME_ARRAY_CHUNKS * size_of::<usize>(),
+            id as u32 + 1,
+        );
+
+        self.names.insert(name.into(), id as u32);
+        id as u32
+    }
+
+    /// Allocates a new UObject and appends it to the object array.
+    pub fn object(&mut self, class: usize, name: &str, outer: usize) -> usize {
+        let ptr = self.alloc(OBJECT_SIZE);
+        let index = self.objects.len();
+        assert!(index < MAX_OBJECTS, "Too many objects");
+        self.objects.push(ptr);
+
+        let of = self.config.uobject.clone();
+        let id = self.name(name);
+        self.write(ptr + of.index, index as u32);
+        self.write(ptr + of.class, class);
+        self.write_name(ptr + of.name, id);
+        self.write(ptr + of.outer, outer);
+
+        let item = self.object_chunk + index * self.config.fuobject_item.size;
+        self.write(item, ptr);
+        self.write(
+            BASE + OBJECTS_OFFSET + size_of::<usize>() * 2 + size_of::<u32>(),
+            self.objects.len() as u32,
+        );
+
+        ptr
+    }
+
+    pub fn package(&mut self, name: &str) -> usize {
+        self.object(self.core.package_class, name, 0)
+    }
+
+    fn ustruct(
+        &mut self,
+        class: usize,
+        outer: usize,
+        name: &str,
+        parent: usize,
+        size: u32,
+        align: u32,
+    ) -> usize {
+        let ptr = self.object(class, name, outer);
+        let of = self.config.ustruct.clone();
+        self.write(ptr + of.super_struct, parent);
+        self.write(ptr + of.props_size, size);
+        self.write(ptr + of.props_size + size_of::<u32>(), align);
+
+        ptr
+    }
+
+    pub fn class(&mut self, outer: usize, name: &str, parent: usize, size: u32) -> usize {
+        self.ustruct(self.core.class, outer, name, parent, size, 8)
+    }
+
+    pub fn script_struct(&mut self, outer: usize, name: &str, size: u32, align: u32) -> usize {
+        self.ustruct(self.core.script_struct, outer, name, 0, size, align)
+    }
+
+    pub fn function(&mut self, owner: usize, name: &str, flags: FunctionFlags) -> usize {
+        let ptr = self.ustruct(self.core.function, owner, name, 0, 0, 1);
+        self.write(ptr + self.config.ufunction.flags, flags.bits());
+        if flags.contains(FunctionFlags::Native) {
+            self.write(ptr + self.config.ufunction.func, BASE + TEXT_OFFSET);
+        }
+        self.link_child(owner, ptr);
+
+        ptr
+    }
+
+    pub fn enumeration(&mut self, outer: usize, name: &str, variants: &[(&str, i64)]) -> usize {
+        let ptr = self.object(self.core.uenum, name, outer);
+
+        let pair_size = self.config.fname.pair_size();
+        let data = self.alloc(variants.len() * pair_size);
+        for (i, (variant, value)) in variants.iter().enumerate() {
+            let id = self.name(&format!("{name}::{variant}"));
+            self.write_name(data + i * pair_size, id);
+            self.write(data + (i + 1) * pair_size - size_of::<i64>(), *value);
+        }
+
+        let names = ptr + self.config.uenum.names;
+        self.write(names, data);
+        self.write(names + size_of::<usize>(), variants.len() as u32);
+        self.write(names + size_of::<usize>() + 4, variants.len() as u32);
+
+        ptr
+    }
+
+    /// Appends a property to the end of `owner` property list.
+    pub fn property(&mut self, owner: usize, name: &str, prop: Prop, offset: usize) -> usize {
+        self.param(owner, name, prop, offset, PropertyFlags::empty())
+    }
+
+    pub fn param(
+        &mut self,
+        owner: usize,
+        name: &str,
+        prop: Prop,
+        offset: usize,
+        flags: PropertyFlags,
+    ) -> usize {
+        let field = self.ffield(owner, name, prop, offset, flags);
+        match self.config.property_model {
+            PropertyModel::FField => match self.last_fields.insert(owner, field) {
+                Some(last) => self.write(last + self.config.ffield.next, field),
+                None => self.write(owner + self.config.ustruct.children_props, field),
+            },
+            PropertyModel::UProperty => self.link_child(owner, field),
+        }
+
+        field
+    }
+
+    /// Appends `child` to the `UField` list of `owner`.
+    fn link_child(&mut self, owner: usize, child: usize) {
+        let Some(children) = self.config.ustruct.children else {
+            return;
+        };
+
+        match self.last_children.insert(owner, child) {
+            Some(last) => self.write(last + self.config.ufield.next, child),
+            None => self.write(owner + children, child),
+        }
+    }
+
+    fn ffield(
+        &mut self,
+        owner: usize,
+        name: &str,
+        prop: Prop,
+        offset: usize,
+        flags: PropertyFlags,
+    ) -> usize {
+        let of = self.config.fproperty.clone();
+        let class = self.field_class(prop.class());
+
+        let ptr = match self.config.property_model {
+            PropertyModel::FField => {
+                let ptr = self.alloc(of.size + 0x20);
+                let id = self.name(name);
+                self.write(ptr + self.config.ffield.class, class);
+                self.write_name(ptr + self.config.ffield.name, id);
+
+                ptr
+            }
+            PropertyModel::UProperty => self.object(class, name, owner),
+        };
+
+        self.write(ptr + of.array_dim, 1u32);
+        self.write(ptr + of.element_size, prop.elem_size() as u32);
+        self.write(ptr + of.flags, flags.bits());
+        self.write(ptr + of.offset, offset as u32);
+
+        let extra = ptr + of.size;
+        match prop {
+            Prop::Bool {
+                byte_mask,
+                field_mask,
+            } => self.write(extra, [1, 0, byte_mask, field_mask]),
+            Prop::Object(target)
+            | Prop::Weak(target)
+            | Prop::Soft(target)
+            | Prop::Multicast(target)
+            | Prop::Struct(target, _) => self.write(extra, target),
+            Prop::Enum(target, _) => self.write(extra + size_of::<usize>(), target),
+            Prop::FieldPath(class) => {
+                let class = self.field_class(class);
+                self.write(extra, class);
+            }
+            Prop::Array(inner) => {
+                let inner = self.ffield(ptr, name, *inner, 0, PropertyFlags::empty());
+                self.write(extra, inner);
+            }
+            _ => (),
+        }
+
+        ptr
+    }
+
+    fn field_class(&mut self, name: &'static str) -> usize {
+        if let Some(class) = self.field_classes.get(name) {
+            return *class;
+        }
+
+        // UProperty classes are regular classes derived from `CoreUObject.Property`.
+        let ptr = match self.config.property_model {
+            PropertyModel::FField => {
+                let ptr = self.alloc(0x40);
+                let id = self.name(name);
+                self.write_name(ptr, id);
+
+                ptr
+            }
+            PropertyModel::UProperty => {
+                let parent = match name {
+                    "Property" => self.core.field,
+                    _ => self.field_class("Property"),
+                };
+                let size = self.config.fproperty.size as u32;
+                self.class(self.core.package, name, parent, size)
+            }
+        };
+        self.field_classes.insert(name, ptr);
+
+        ptr
+    }
+
+    pub fn finish(self) -> Snapshot {
+        let mut snapshot = Snapshot::new(BASE);
+        for (address, data) in self.memory.iter() {
+            snapshot.insert(*address, data);
+        }
+
+        snapshot
+    }
+
+    /// Dumper that reads the finished image.
+    pub fn dumper(self) -> Dumper {
+        let options = DumperOptions {
+            process_id: None,
+            names: self.names_offset(),
+            objects: self.objects_offset(),
+            merge: HashMap::new(),
+            allow_cycles: false,
+        };
+        let (base, config) = (self.base(), self.config.clone());
+        Dumper::new(options, config, Box::new(self.finish()), base)
+    }
+
+    pub fn dump(self) -> Result<Sdk> {
+        self.dumper().run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Image, Prop};
+    use crate::{
+        codegen::{Codegen, CppCodegen, CppOptions, RustCodegen, RustOptions},
+        config::{OfFName, OfFUObjectItem, OfUObject},
+        engine::{FunctionFlags, PropertyFlags},
+        sdk::{Field, Object, PropertyKind},
+        Config, Sdk,
+    };
+    use std::{collections::HashMap, fs, path::PathBuf, process::Command, thread};
+    use ucore::fqn;
+
+    /// Actor with a property of every supported kind and a native function.
+    fn actor_image() -> Image {
+        let mut image = Image::new(Config::default());
+        let core = image.core.object;
+        let vector = image.core.vector;
+
+        let guid = image.script_struct(image.core.package, "Guid", 0x10, 4);
+        for (i, part) in ["A", "B", "C", "D"].into_iter().enumerate() {
+            image.property(guid, part, Prop::Int32, i * 4);
+        }
+
+        let engine = image.package("/Script/Engine");
+        let role = image.enumeration(
+            engine,
+            "ENetRole",
+            &[("ROLE_None", 0), ("ROLE_Authority", 3), ("ROLE_MAX", 4)],
+        );
+        let actor = image.class(engine, "Actor", core, 0xE8);
+        image.property(actor, "Instigator", Prop::Object(actor), 0x28);
+        image.property(actor, "Location", Prop::Struct(vector, 0xC), 0x30);
+        image.property(actor, "Role", Prop::Enum(role, 1), 0x3C);
+        let hidden = Prop::Bool {
+            byte_mask: 1,
+            field_mask: 1,
+        };
+        image.property(actor, "bHidden", hidden, 0x3D);
+        let replicates = Prop::Bool {
+            byte_mask: 2,
+            field_mask: 2,
+        };
+        image.property(actor, "bReplicates", replicates, 0x3D);
+        image.property(actor, "Tags", Prop::Array(Prop::Name.into()), 0x40);
+        image.property(actor, "Label", Prop::Str, 0x50);
+        image.property(actor, "Guid", Prop::Struct(guid, 0x10), 0x60);
+        image.property(actor, "DisplayName", Prop::Text, 0x70);
+        image.property(actor, "Owner", Prop::Weak(actor), 0x88);
+        image.property(actor, "Asset", Prop::Soft(core), 0x90);
+        let signature = image.function(
+            actor,
+            "OnDestroyed__DelegateSignature",
+            FunctionFlags::Delegate | FunctionFlags::MulticastDelegate,
+        );
+        image.param(
+            signature,
+            "DestroyedActor",
+            Prop::Object(actor),
+            0,
+            PropertyFlags::Parm,
+        );
+        image.property(actor, "OnDestroyed", Prop::Multicast(signature), 0xB8);
+        image.property(actor, "Attribute", Prop::FieldPath("Property"), 0xC8);
+
+        let function = image.function(
+            actor,
+            "K2_GetActorLocation",
+            FunctionFlags::Native | FunctionFlags::Public,
+        );
+        image.param(
+            function,
+            "ReturnValue",
+            Prop::Struct(vector, 0xC),
+            0,
+            PropertyFlags::Parm | PropertyFlags::OutParm | PropertyFlags::ReturnParm,
+        );
+
+        let statics = image.class(engine, "GameplayStatics", core, 0x28);
+        let function = image.function(
+            statics,
+            "GetTimeSeconds",
+            FunctionFlags::Native | FunctionFlags::Static | FunctionFlags::Public,
+        );
+        image.param(
+            function,
+            "ReturnValue",
+            Prop::Float32,
+            0,
+            PropertyFlags::Parm | PropertyFlags::OutParm | PropertyFlags::ReturnParm,
+        );
+        let function = image.function(
+            statics,
+            "GetAccurateRealTime",
+            FunctionFlags::Native | FunctionFlags::Static | FunctionFlags::Public,
+        );
+        image.param(
+            function,
+            "Seconds",
+            Prop::Int32,
+            0,
+            PropertyFlags::Parm | PropertyFlags::OutParm | PropertyFlags::ReferenceParm,
+        );
+
+        image
+    }
+
+    fn output_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("usdk-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn test_reflection() {
+        let sdk = actor_image().dump().unwrap();
+
+        assert_eq!(sdk.packages.node_count(), 2);
+        let info = sdk.lookup(&fqn!(Engine.Actor)).unwrap();
+        let Object::Struct(actor) = &*info.ptr else {
+            panic!("Actor must be a struct")
+        };
+        assert_eq!(actor.ident, "AActor");
+        assert_eq!(actor.parent, Some(fqn!(CoreUObject.Object)));
+        assert_eq!(actor.functions.borrow().len(), 2);
+
+        let kinds = actor
+            .fields
+            .iter()
+            .filter_map(|f| match f {
+                Field::Property { name, kind, .. } => Some((name.as_str(), kind.clone())),
+                Field::Bitfields(_) => None,
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(kinds["Instigator"], PropertyKind::Ptr(fqn!(Engine.Actor)));
+        assert_eq!(
+            kinds["Location"],
+            PropertyKind::Inline(fqn!(CoreUObject.Vector))
+        );
+        assert_eq!(kinds["Role"], PropertyKind::Inline(fqn!(Engine.ENetRole)));
+        assert_eq!(kinds["Tags"], PropertyKind::Vec(PropertyKind::Name.into()));
+        assert_eq!(kinds["DisplayName"], PropertyKind::Text);
+        assert_eq!(kinds["Owner"], PropertyKind::Weak(fqn!(Engine.Actor)));
+        assert_eq!(kinds["Asset"], PropertyKind::Soft(fqn!(CoreUObject.Object)));
+        assert_eq!(
+            kinds["OnDestroyed"],
+            PropertyKind::MulticastDelegate(fqn!(Engine.Actor.OnDestroyed__DelegateSignature))
+        );
+        assert_eq!(
+            kinds["Attribute"],
+            PropertyKind::FieldPath("Property".into())
+        );
+        assert!(actor
+            .fields
+            .iter()
+            .any(|f| matches!(f, Field::Bitfields(g) if g.items.len() == 2)));
+    }
+
+    #[test]
+    fn test_json() {
+        let sdk = actor_image().dump().unwrap();
+
+        let json = serde_json::to_value(&sdk).unwrap();
+        let engine = json["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["ident"] == "Engine")
+            .unwrap();
+        assert_eq!(engine["dependencies"], serde_json::json!(["CoreUObject"]));
+        let actor = engine["objects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find_map(|o| o.get("Struct").filter(|c| c["fqn"] == "Engine.Actor"))
+            .unwrap();
+        assert_eq!(actor["parent"], "CoreUObject.Object");
+        assert_eq!(
+            actor["fields"][0]["Property"]["kind"]["Ptr"],
+            "Engine.Actor"
+        );
+        let function = actor["functions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["ident"] == "K2_GetActorLocation")
+            .unwrap();
+        assert_eq!(function["args"][0]["flags"], "Parm | OutParm | ReturnParm");
+    }
+
+    #[test]
+    fn test_rust_codegen() {
+        let sdk = actor_image().dump().unwrap();
+        let path = output_dir("rust");
+        let options = RustOptions {
+            path: path.clone(),
+            glam: false,
+        };
+        RustCodegen::new(&sdk, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+
+        let engine = fs::read_to_string(path.join("crates/Engine/Engine.rs")).unwrap();
+        assert!(engine.contains("pub struct AActor : pub UObject {"));
+        assert!(engine.contains("pub Location: FVector, // 0x30(0xC)"));
+        assert!(engine.contains("pub Guid: FGuid, // 0x60(0x10)"));
+        assert!(engine.contains("pub DisplayName: FText, // 0x70(0x18)"));
+        assert!(engine.contains("pub Owner: TWeakObjectPtr<AActor>, // 0x88(0x8)"));
+        assert!(engine.contains("pub Asset: TSoftObjectPtr<UObject>, // 0x90(0x28)"));
+        assert!(engine.contains(
+            "pub OnDestroyed: TMulticastScriptDelegate<AActor_OnDestroyed__DelegateSignature>, // 0xB8(0x10)"
+        ));
+        assert!(engine.contains(
+            "pub struct AActor_OnDestroyed__DelegateSignature {\n    pub DestroyedActor: Option<Ptr<AActor>>,\n}"
+        ));
+        assert!(!engine.contains("fn OnDestroyed__DelegateSignature"));
+        assert!(engine.contains("pub Attribute: TFieldPath<FProperty>, // 0xC8(0x20)"));
+        assert!(engine.contains("pub const ROLE_Authority: Self = Self(3i64 as u8);"));
+        assert!(!engine.contains("ROLE_MAX"));
+        assert!(engine.contains(
+            "fn K2_GetActorLocation() -> [AActor_K2_GetActorLocationResult; ReturnValue: FVector]"
+        ));
+
+        // Saved model must generate exactly the same code.
+        sdk.save_json(path.join("sdk.json")).unwrap();
+        let loaded = Sdk::load_json(path.join("sdk.json")).unwrap();
+        assert_eq!(loaded.packages.edge_count(), sdk.packages.edge_count());
+        let options = RustOptions {
+            path: path.join("loaded"),
+            glam: false,
+        };
+        RustCodegen::new(&loaded, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+        for file in [
+            "crates/Engine/Engine.rs",
+            "crates/CoreUObject/CoreUObject.rs",
+        ] {
+            let reloaded = fs::read_to_string(options.path.join(file)).unwrap();
+            assert_eq!(reloaded, fs::read_to_string(path.join(file)).unwrap());
+        }
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_proxy_constants() {
+        let image = actor_image();
+        let (names, objects) = (image.names_offset(), image.objects_offset());
+        let sdk = image.dump().unwrap();
+        let path = output_dir("proxy");
+        let options = RustOptions {
+            path: path.clone(),
+            glam: false,
+        };
+        RustCodegen::new(&sdk, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+
+        let proxy = fs::read_to_string(path.join("uproxy/uproxy.rs")).unwrap();
+        assert!(proxy.contains("pub const FNAME_ENTRY_HEADER: usize = 0x0;"));
+        assert!(proxy.contains("pub const FNAME_LEN_BIT: u32 = 6;"));
+        assert!(proxy.contains("pub const FUOBJECT_ITEM_SIZE: usize = 0x18;"));
+        assert!(proxy.contains("pub const FUOBJECT_ITEM_SERIAL: usize = 0x10;"));
+        assert!(proxy.contains("pub const USTRUCT_SUPER_STRUCT: usize = 0x40;"));
+        assert!(proxy.contains("pub const USTRUCT_CHILD_PROPERTIES: Option<usize> = Some(0x50);"));
+        assert!(proxy.contains(&format!(
+            "pub const NAMES_OFFSET: Option<usize> = Some({:#X});",
+            names
+        )));
+        assert!(proxy.contains(&format!(
+            "pub const OBJECTS_OFFSET: Option<usize> = Some({:#X});",
+            objects
+        )));
+        assert!(proxy.contains("pub const WORLD_OFFSET: Option<usize> = None;"));
+        let lib = fs::read_to_string(path.join("lib.rs")).unwrap();
+        assert!(lib.contains("pub fn layout() -> ucore::EngineLayout {"));
+        assert!(!lib.contains("Default::default()"));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_cpp_codegen() {
+        let sdk = actor_image().dump().unwrap();
+        let path = output_dir("cpp");
+        let options = CppOptions { path: path.clone() };
+        CppCodegen::new(&sdk, &options).unwrap().generate().unwrap();
+
+        let basic = fs::read_to_string(path.join("Basic.hpp")).unwrap();
+        assert!(basic.contains("static_assert(sizeof(FName) == 0x8);"));
+        let engine = fs::read_to_string(path.join("Engine.hpp")).unwrap();
+        assert!(engine.contains("#include \"CoreUObject.hpp\""));
+        assert!(engine.contains("enum class ENetRole : uint8_t {\n    ROLE_None = 0,"));
+        assert!(engine.contains("struct AActor : public UObject {"));
+        assert!(engine.contains("    FVector Location; // 0x30(0xC)"));
+        assert!(engine.contains("    TWeakObjectPtr<AActor> Owner; // 0x88(0x8)"));
+        assert!(engine.contains("    FMulticastScriptDelegate OnDestroyed; // 0xB8(0x10)"));
+        assert!(!engine.contains("OnDestroyed__DelegateSignature"));
+        assert!(engine.contains("    uint8_t bReplicates : 1; // 0x3D(1:1)\n    uint8_t : 6;"));
+        assert!(engine.contains("static_assert(sizeof(AActor) == 0xE8);"));
+        assert!(engine.contains("static_assert(offsetof(AActor, Guid) == 0x60);"));
+        assert!(engine.contains("    FVector K2_GetActorLocation();"));
+        assert!(engine.contains("        alignas(0x4) FVector ReturnValue;"));
+        assert!(engine.contains("    return Params.ReturnValue;"));
+
+        assert!(engine.contains("    static float GetTimeSeconds();"));
+        assert!(engine.contains(
+            "    static UObject* Default = FindObject(\"Engine.Default__GameplayStatics\");"
+        ));
+        assert!(engine.contains("    ProcessEvent(Default, Function, &Params);"));
+        assert!(engine.contains("    static void GetAccurateRealTime(int32_t& Seconds);"));
+        assert!(engine.contains(
+            "    Params.Seconds = Seconds;\n    ProcessEvent(Default, Function, &Params);\n    Seconds = Params.Seconds;"
+        ));
+
+        let core = fs::read_to_string(path.join("CoreUObject.hpp")).unwrap();
+        assert!(core.contains("struct UObject {\n    uint8_t Pad_0[0x28]; // 0x0(0x28)"));
+
+        // Headers must compile, skipped when there is no C++ compiler around.
+        fs::write(
+            path.join("main.cpp"),
+            "#include \"Engine.hpp\"\nSDK::UObject* SDK::FindObject(const char*) { return nullptr; }\n",
+        )
+        .unwrap();
+        let output = Command::new("g++")
+            .args(["-std=c++17", "-fsyntax-only", "main.cpp"])
+            .current_dir(&path)
+            .output();
+        match output {
+            Ok(output) => assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            ),
+            Err(err) => eprintln!("Skipped compiling C++ headers: {err}"),
+        }
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_properties() {
+        let config: Config = toml::from_str(include_str!("../../configs/4_22.toml")).unwrap();
+        let mut image = Image::new(config);
+        let vector = image.core.vector;
+
+        let engine = image.package("/Script/Engine");
+        let actor = image.class(engine, "Actor", image.core.object, 0x48);
+        image.property(actor, "Location", Prop::Struct(vector, 0xC), 0x28);
+        let function = image.function(actor, "K2_SetActorLocation", FunctionFlags::Native);
+        image.param(
+            function,
+            "NewLocation",
+            Prop::Struct(vector, 0xC),
+            0,
+            PropertyFlags::Parm,
+        );
+        image.property(actor, "Tags", Prop::Array(Prop::Name.into()), 0x38);
+
+        let sdk = image.dump().unwrap();
+
+        let info = sdk.lookup(&fqn!(Engine.Actor)).unwrap();
+        let Object::Struct(actor) = &*info.ptr else {
+            panic!("Actor must be a struct")
+        };
+        let fields = actor
+            .fields
+            .iter()
+            .map(|f| match f {
+                Field::Property { name, kind, .. } => (name.as_str(), kind.clone()),
+                Field::Bitfields(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                ("Location", PropertyKind::Inline(fqn!(CoreUObject.Vector))),
+                ("Tags", PropertyKind::Vec(PropertyKind::Name.into())),
+            ]
+        );
+
+        let functions = actor.functions.borrow();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].args.len(), 1);
+        assert_eq!(functions[0].args[0].name, "NewLocation");
+        drop(functions);
+
+        // There is no ChildProperties to pass to `ucore`.
+        let path = output_dir("legacy");
+        let options = RustOptions {
+            path: path.clone(),
+            glam: false,
+        };
+        RustCodegen::new(&sdk, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+        let proxy = fs::read_to_string(path.join("uproxy/uproxy.rs")).unwrap();
+        assert!(proxy.contains("pub const USTRUCT_CHILD_PROPERTIES: Option<usize> = None;"));
+        assert!(!proxy.contains("FNAME_BLOCKS"));
+        let lib = fs::read_to_string(path.join("lib.rs")).unwrap();
+        assert!(!lib.contains("pub fn layout()"));
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_fname_layouts() {
+        // Case preserving entries start with ComparisonId and are aligned to 4 bytes.
+        let dump = |fname: OfFName, uobject: OfUObject| {
+            let config = Config {
+                stride: if fname.display_index.is_some() { 4 } else { 2 },
+                fname,
+                uobject,
+                ..Default::default()
+            };
+            let mut image = Image::new(config);
+            let engine = image.package("/Script/Engine");
+            image.enumeration(engine, "ENetRole", &[("ROLE_None", 0), ("ROLE_Proxy", 1)]);
+            let actor = image.class(engine, "Actor", image.core.object, 0x30);
+            if image.config.fname.outline_number() {
+                let id = image.numbered_name("Actor", 1);
+                image.write_name(actor + image.config.uobject.name, id);
+            }
+
+            image.dump().unwrap()
+        };
+
+        // WITH_CASE_PRESERVING_NAME shifts UObject::Outer.
+        let sdk = dump(
+            OfFName {
+                size: 0xC,
+                index: 0,
+                display_index: Some(4),
+                number: Some(8),
+                ..Default::default()
+            },
+            OfUObject {
+                outer: 0x28,
+                ..Config::default().uobject
+            },
+        );
+        assert!(sdk.lookup(&fqn!(Engine.Actor)).is_some());
+        let info = sdk.lookup(&fqn!(Engine.ENetRole)).unwrap();
+        let Object::Enum(role) = &*info.ptr else {
+            panic!("ENetRole must be an enum")
+        };
+        assert_eq!(role.variants[1], ("ROLE_Proxy".into(), 1));
+
+        let path = output_dir("fname");
+        let options = RustOptions {
+            path: path.clone(),
+            glam: false,
+        };
+        RustCodegen::new(&sdk, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+        let workspace = fs::read_to_string(path.join("Cargo.toml")).unwrap();
+        assert!(workspace.contains(r#"features = ["case_preserving_name"]"#));
+        let proxy = fs::read_to_string(path.join("uproxy/uproxy.rs")).unwrap();
+        assert!(proxy.contains("pub const FNAME_SIZE: usize = 0xC;"));
+        fs::remove_dir_all(&path).unwrap();
+
+        let sdk = dump(
+            OfFName {
+                size: 0x8,
+                index: 0,
+                display_index: Some(4),
+                number: None,
+                ..Default::default()
+            },
+            Config::default().uobject,
+        );
+        assert!(sdk.lookup(&fqn!(Engine.Actor_1)).is_some());
+
+        // `bIsWide` goes first and `Len` takes the rest of the header.
+        let sdk = dump(
+            OfFName {
+                len_bit: 1,
+                ..Default::default()
+            },
+            Config::default().uobject,
+        );
+        assert!(sdk.lookup(&fqn!(Engine.Actor)).is_some());
+        RustCodegen::new(&sdk, &options)
+            .unwrap()
+            .generate()
+            .unwrap();
+        let proxy = fs::read_to_string(path.join("uproxy/uproxy.rs")).unwrap();
+        assert!(proxy.contains("pub const FNAME_LEN_BIT: u32 = 1;"));
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_independent_dumps() {
+        let dump = |item_size: usize, class: &'static str| {
+            let config = Config {
+                fuobject_item: OfFUObjectItem {
+                    size: item_size,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut image = Image::new(config);
+            let game = image.package("/Script/Game");
+            image.class(game, class, image.core.object, 0x28);
+
+            let mut dumper = image.dumper();
+
+            thread::spawn(move || dumper.run().unwrap().owned.keys().copied().collect())
+        };
+
+        let (first, second) = (dump(0x18, "Pawn"), dump(0x20, "Character"));
+        let (first, second): (Vec<_>, Vec<_>) = (first.join().unwrap(), second.join().unwrap());
+        assert!(first.contains(&fqn!(Game.Pawn)) && !first.contains(&fqn!(Game.Character)));
+        assert!(second.contains(&fqn!(Game.Character)) && !second.contains(&fqn!(Game.Pawn)));
+    }
+}
@@ -1,12 +1,10 @@
 use anyhow::{Context, Result};
 use names::NamePool;
-use sdk::Sdk;
 use std::{
     collections::HashMap,
     mem::{size_of, zeroed},
     ptr::addr_of_mut,
     slice::from_raw_parts_mut,
-    sync::OnceLock,
 };
 
 pub mod codegen {
@@ -21,24 +19,38 @@ pub mod codegen {
         fn generate(&self) -> Result<()>;
     }
 
+    mod cpp;
     mod rust;
     use anyhow::Result;
+    pub use cpp::*;
     pub use rust::*;
 }
 
 pub(crate) mod cycles;
+mod diff;
+mod discovery;
 mod engine;
+#[cfg(test)]
+mod fixture;
 mod names;
 mod objects;
+mod probe;
 mod process;
 mod sdk;
+mod snapshot;
 mod utils;
 
 mod config;
 pub use config::Config;
+pub use diff::{diff_sdk, Change, ObjectDiff, SdkDiff};
+pub use discovery::{discover_offsets, DiscoveredOffsets};
+pub use probe::{probe_config, ProbedConfig};
+pub use sdk::Sdk;
+pub use snapshot::{Recorder, Snapshot};
 
 pub struct DumperOptions {
-    pub process_id: u32,
+    /// `None` when reading from a snapshot
+    pub process_id: Option<u32>,
     pub names: usize,
     pub objects: usize,
     /// Options to merge two packages together to avoid cyclic dependencies
@@ -48,48 +60,58 @@ pub struct DumperOptions {
 
 pub fn run(
     options: DumperOptions,
-    offsets: Config,
+    config: Config,
     external: Box<dyn External>,
     base: usize,
 ) -> Result<Sdk> {
-    let state = State {
-        names: OnceLock::new(),
-        config: offsets,
-        external,
-        options,
-        base,
-    };
-    _ = STATE.set(state);
-
-    let names = names::dump_names()?;
-    _ = State::get().names.set(names);
-
-    let objects = objects::dump_objects()?;
-    let sdk = process::process(&objects)?;
-    Ok(sdk)
+    Dumper::new(options, config, external, base).run()
 }
 
-static STATE: OnceLock<State> = OnceLock::new();
-
-struct State {
-    names: OnceLock<NamePool>,
+/// State of a single dump, owns everything that is needed to read the game memory.
+pub struct Dumper {
+    names: NamePool,
     external: Box<dyn External>,
     options: DumperOptions,
     config: Config,
     base: usize,
 }
 
-impl State {
-    fn get_name(&self, id: u32) -> Result<&str> {
-        self.names
-            .get()
-            .unwrap()
-            .get(id)
-            .context("Name was not found")
+impl Dumper {
+    pub fn new(
+        options: DumperOptions,
+        config: Config,
+        external: Box<dyn External>,
+        base: usize,
+    ) -> Self {
+        Self {
+            names: NamePool::default(),
+            external,
+            options,
+            config,
+            base,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Sdk> {
+        self.names = names::dump_names(self)?;
+
+        let objects = objects::dump_objects(self)?;
+        let sdk = process::process(self, &objects)?;
+        Ok(sdk)
     }
 
-    fn get() -> &'static Self {
-        STATE.get().unwrap()
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn get_name(&self, id: u32) -> Result<&'static str> {
+        self.names.get(id).context("Name was not found")
+    }
+
+    #[inline]
+    fn read<T>(&self, address: usize) -> Result<T> {
+        self.external.read(address)
     }
 }
 
@@ -1,85 +1,225 @@
-use crate::State;
-use anyhow::Result;
+use crate::{
+    config::{NamePoolKind, OfFNameEntry},
+    Config, Dumper, External,
+};
+use anyhow::{ensure, Context, Result};
 use log::{debug, info};
-use std::{collections::HashMap, mem::size_of};
-use ucore::{FNameEntry, FNameEntryHeader};
+use std::{
+    collections::{HashMap, HashSet},
+    mem::size_of,
+    sync::Mutex,
+};
 
 const FNAME_BLOCK_OFFSET_BITS: u32 = 16;
 const FNAME_BLOCK_OFFSETS: u32 = 1 << FNAME_BLOCK_OFFSET_BITS;
 
+// TStaticIndirectArrayThreadSafeRead<FNameEntry, 2 * 1024 * 1024, 16384>
+const NAME_ENTRIES_PER_CHUNK: usize = 16384;
+const NAME_MAX_CHUNKS: usize = 2 * 1024 * 1024 / NAME_ENTRIES_PER_CHUNK;
+const NAME_SIZE: usize = 1024;
+const PAGE_SIZE: usize = 0x1000;
+
+// Names have to outlive the dump because `Fqn` only holds static strings,
+// interning them avoids leaking the same names again when dumping several times.
+static INTERNED: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+pub(crate) fn intern(name: &str) -> &'static str {
+    let mut lock = INTERNED.lock().unwrap();
+    let interned = lock.get_or_insert_with(HashSet::new);
+
+    match interned.get(name) {
+        Some(name) => name,
+        None => {
+            let name = Box::leak(name.into());
+            interned.insert(name);
+            name
+        }
+    }
+}
+
 #[derive(Default, Debug)]
-pub(crate) struct NamePool(pub HashMap<u32, String>);
+pub(crate) struct NamePool(pub HashMap<u32, &'static str>);
 
 impl NamePool {
-    pub fn insert(&mut self, block: u32, offset: u32, name: String) {
-        self.0
-            .insert((block << FNAME_BLOCK_OFFSET_BITS) | offset, name);
+    pub fn insert(&mut self, id: u32, name: &str) {
+        self.0.insert(id, intern(name));
     }
 
-    pub fn get(&self, id: u32) -> Option<&str> {
-        self.0.get(&id).map(|s| s.as_str())
+    pub fn get(&self, id: u32) -> Option<&'static str> {
+        self.0.get(&id).copied()
     }
 }
 
-pub(crate) fn dump_names() -> Result<NamePool> {
-    let State {
-        options,
-        external: proc,
-        base,
-        config: offsets,
-        ..
-    } = State::get();
-
-    let pool_ptr = *base + options.names;
-    let current_block = proc.read::<u32>(pool_ptr + size_of::<usize>())?;
-    let current_block_byte_cursor =
-        proc.read::<u32>(pool_ptr + size_of::<usize>() + size_of::<u32>())?;
-
-    info!("FNamePool: CurrentBlock = {current_block} CurrentBlockByteCursor = {current_block_byte_cursor}");
-
-    let mut pool = NamePool::default();
-    let mut total_names = 0;
-    for idx in 0..current_block + 1 {
-        let size = if idx == current_block {
-            current_block_byte_cursor
-        } else {
-            offsets.stride * FNAME_BLOCK_OFFSETS
-        };
-        let block = dump_block(pool_ptr, idx as usize, size as usize)?;
-
-        unsafe {
-            let mut entry = block.as_ptr().cast::<FNameEntry>();
-            while entry.cast::<u8>().offset_from(block.as_ptr()) < size as isize {
-                let name = (*entry).to_str();
-
-                pool.insert(
-                    idx,
-                    entry.cast::<u8>().offset_from(block.as_ptr()) as u32 / offsets.stride,
-                    name.into_owned(),
-                );
-
-                entry = entry
-                    .cast::<u8>()
-                    .add(size_of::<FNameEntryHeader>() + (*entry).size_in_bytes())
-                    .cast();
+/// Reads names out of the global name table of a specific layout.
+pub(crate) trait NameReader {
+    /// Reads every name from the table located at `table`.
+    fn dump(&self, proc: &dyn External, table: usize) -> Result<NamePool>;
+
+    /// Reads a single name without dumping the whole table.
+    fn read(&self, proc: &dyn External, table: usize, id: u32) -> Result<String>;
+}
+
+pub(crate) fn name_reader(config: &Config) -> Result<Box<dyn NameReader>> {
+    Ok(match config.name_pool {
+        NamePoolKind::FNamePool => Box::new(FNamePoolReader::new(config)),
+        NamePoolKind::TNameEntryArray => Box::new(TNameEntryArrayReader {
+            entry: config
+                .fname_entry
+                .clone()
+                .context("FNameEntry offsets are required by TNameEntryArray")?,
+        }),
+    })
+}
+
+pub(crate) fn dump_names(dumper: &Dumper) -> Result<NamePool> {
+    let reader = name_reader(&dumper.config)?;
+    reader.dump(&*dumper.external, dumper.base + dumper.options.names)
+}
+
+/// UE4.23+ block based name pool, ids are block index and offset within the block.
+pub(crate) struct FNamePoolReader {
+    pub stride: u32,
+    /// Entries with empty header hold `FNumberedData` of a numbered name.
+    pub outline_number: bool,
+    /// Offset of `FNameEntryHeader` in the entry.
+    pub header: usize,
+    /// Offset of `Blocks` in the pool, `CurrentBlock` and `CurrentByteCursor` go right before it.
+    pub blocks: usize,
+    pub wide_bit: u32,
+    pub len_bit: u32,
+}
+
+impl FNamePoolReader {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            stride: config.stride,
+            outline_number: config.fname.outline_number(),
+            header: config.fname.header_offset(),
+            blocks: config.fname.pool_blocks,
+            wide_bit: config.fname.wide_bit,
+            len_bit: config.fname.len_bit,
+        }
+    }
+
+    fn block(&self, proc: &dyn External, pool: usize, idx: usize) -> Result<usize> {
+        proc.read::<usize>(pool + self.blocks + idx * size_of::<usize>())
+    }
+
+    /// Decodes `FNameEntryHeader` into the length and whether the name is wide.
+    fn decode_header(&self, header: u16) -> (usize, bool) {
+        (
+            (header >> self.len_bit) as usize,
+            (header >> self.wide_bit) & 1 != 0,
+        )
+    }
+}
+
+// FNumberedData { Id: [u8; 4], Number: [u8; 4] }
+const NUMBERED_DATA_SIZE: usize = 8;
+
+fn numbered_name(base: &str, number: u32) -> String {
+    // Number is stored incremented by one, zero means no number.
+    format!("{base}_{}", number.wrapping_sub(1))
+}
+
+fn decode_name(wide: bool, data: &[u8]) -> String {
+    if wide {
+        let wide = data
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16_lossy(&wide)
+    } else {
+        String::from_utf8_lossy(data).into_owned()
+    }
+}
+
+impl NameReader for FNamePoolReader {
+    fn dump(&self, proc: &dyn External, pool_ptr: usize) -> Result<NamePool> {
+        let [current_block, current_block_byte_cursor] =
+            proc.read::<[u32; 2]>(pool_ptr + self.blocks - size_of::<u32>() * 2)?;
+
+        info!("FNamePool: CurrentBlock = {current_block} CurrentBlockByteCursor = {current_block_byte_cursor}");
+
+        let mut pool = NamePool::default();
+        let mut numbered = vec![];
+        let mut total_names = 0;
+        for idx in 0..current_block + 1 {
+            let size = if idx == current_block {
+                current_block_byte_cursor
+            } else {
+                self.stride * FNAME_BLOCK_OFFSETS
+            };
+            let address = self.block(proc, pool_ptr, idx as usize)?;
+            debug!("FNamePool: Dumping block {idx} at address {address:#X}");
+            let block = dump_block(proc, address, size as usize)?;
+
+            let mut offset = 0;
+            while offset < size as usize {
+                let id = (idx << FNAME_BLOCK_OFFSET_BITS) | (offset / self.stride as usize) as u32;
+                let at = offset + self.header;
+                let entry = block
+                    .get(at..at + size_of::<u16>())
+                    .with_context(|| format!("FNamePool entry {id:#X} is out of bounds"))?;
+                let (len, wide) = self.decode_header(u16::from_le_bytes([entry[0], entry[1]]));
+                let data = at + size_of::<u16>();
+
+                let data_size = if self.outline_number && len == 0 {
+                    let data = block
+                        .get(data..data + NUMBERED_DATA_SIZE)
+                        .with_context(|| format!("FNamePool entry {id:#X} is out of bounds"))?;
+                    let [base, number] =
+                        unsafe { data.as_ptr().cast::<[u32; 2]>().read_unaligned() };
+                    numbered.push((id, base, number));
+                    NUMBERED_DATA_SIZE
+                } else {
+                    let bytes = len * if wide { 2 } else { 1 };
+                    let name = block
+                        .get(data..data + bytes)
+                        .with_context(|| format!("FNamePool entry {id:#X} is out of bounds"))?;
+                    pool.insert(id, &decode_name(wide, name));
+                    bytes
+                };
+
+                offset += (self.header + size_of::<u16>() + data_size)
+                    .next_multiple_of(self.stride as usize);
                 total_names += 1;
             }
-        };
+        }
+
+        for (id, base, number) in numbered {
+            if let Some(base) = pool.get(base) {
+                pool.insert(id, &numbered_name(base, number));
+            }
+        }
+
+        info!("Found {total_names} names");
+
+        Ok(pool)
     }
 
-    info!("Found {total_names} names");
+    fn read(&self, proc: &dyn External, pool: usize, id: u32) -> Result<String> {
+        let block = self.block(proc, pool, (id >> FNAME_BLOCK_OFFSET_BITS) as usize)?;
+        ensure!(block != 0, "FNamePool block of name {id:#X} is null");
 
-    Ok(pool)
-}
+        let entry = block + (id & (FNAME_BLOCK_OFFSETS - 1)) as usize * self.stride as usize;
+        let (len, wide) = self.decode_header(proc.read::<u16>(entry + self.header)?);
+        let data = entry + self.header + size_of::<u16>();
 
-fn dump_block(pool: usize, idx: usize, size: usize) -> Result<Box<[u8]>> {
-    let State { external: proc, .. } = State::get();
+        if self.outline_number && len == 0 {
+            let [base, number] = proc.read::<[u32; 2]>(data)?;
+            ensure!(base != id, "Numbered name {id:#X} references itself");
+            return Ok(numbered_name(&self.read(proc, pool, base)?, number));
+        }
 
-    let address = proc.read::<usize>(
-        pool + size_of::<usize>() + size_of::<u32>() * 2 + idx * size_of::<usize>(),
-    )?;
-    debug!("FNamePool: Dumping block {idx} at address {address:#X}");
+        let mut buf = vec![0u8; len * if wide { 2 } else { 1 }];
+        proc.read_buf(data, &mut buf)?;
 
+        Ok(decode_name(wide, &buf))
+    }
+}
+
+fn dump_block(proc: &dyn External, address: usize, size: usize) -> Result<Box<[u8]>> {
     let mut data = Vec::with_capacity(size);
     #[allow(clippy::uninit_vec)]
     unsafe {
@@ -94,3 +234,104 @@ fn dump_block(pool: usize, idx: usize, size: usize) -> Result<Box<[u8]>> {
 
     Ok(data.into_boxed_slice())
 }
+
+/// Pre UE4.23 chunked array of `FNameEntry` pointers, ids are indices in the array.
+/// `table` is the address of `GNames` pointer to the array.
+pub(crate) struct TNameEntryArrayReader {
+    pub entry: OfFNameEntry,
+}
+
+impl TNameEntryArrayReader {
+    fn entry(&self, proc: &dyn External, array: usize, id: u32) -> Result<usize> {
+        let (chunk, idx) = (
+            id as usize / NAME_ENTRIES_PER_CHUNK,
+            id as usize % NAME_ENTRIES_PER_CHUNK,
+        );
+        ensure!(chunk < NAME_MAX_CHUNKS, "Name {id:#X} is out of bounds");
+
+        let chunk = proc.read::<usize>(array + chunk * size_of::<usize>())?;
+        ensure!(chunk != 0, "TNameEntryArray chunk of name {id:#X} is null");
+
+        proc.read::<usize>(chunk + idx * size_of::<usize>())
+    }
+
+    // Names are null terminated, the length is unknown upfront.
+    fn read_entry(&self, proc: &dyn External, entry: usize) -> Result<String> {
+        let wide = proc.read::<u32>(entry + self.entry.index)? & 1 != 0;
+        let data = entry + self.entry.name;
+        let char_size = if wide { 2 } else { 1 };
+
+        let mut buf = vec![];
+        while buf.len() < NAME_SIZE * char_size {
+            // Reads never cross page boundary, the next page might not exist.
+            let address = data + buf.len();
+            let mut part = [0u8; 64];
+            let part = &mut part[..64.min(PAGE_SIZE - address % PAGE_SIZE)];
+            proc.read_buf(address, part)?;
+
+            let end = part
+                .chunks(char_size)
+                .position(|c| c.iter().all(|b| *b == 0))
+                .map(|i| i * char_size);
+            buf.extend_from_slice(&part[..end.unwrap_or(part.len())]);
+            if end.is_some() {
+                break;
+            }
+        }
+
+        Ok(if wide {
+            let wide = buf
+                .chunks(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&wide)
+        } else {
+            String::from_utf8_lossy(&buf).into_owned()
+        })
+    }
+}
+
+impl NameReader for TNameEntryArrayReader {
+    fn dump(&self, proc: &dyn External, table: usize) -> Result<NamePool> {
+        let array = proc.read::<usize>(table)?;
+        let [num_elements, num_chunks] =
+            proc.read::<[u32; 2]>(array + NAME_MAX_CHUNKS * size_of::<usize>())?;
+
+        info!("TNameEntryArray: NumElements = {num_elements} NumChunks = {num_chunks}");
+
+        let mut pool = NamePool::default();
+        for chunk_idx in 0..num_chunks as usize {
+            let chunk = proc.read::<usize>(array + chunk_idx * size_of::<usize>())?;
+            debug!("TNameEntryArray: Dumping chunk {chunk_idx} at address {chunk:#X}");
+
+            let first = chunk_idx * NAME_ENTRIES_PER_CHUNK;
+            let len = (num_elements as usize)
+                .saturating_sub(first)
+                .min(NAME_ENTRIES_PER_CHUNK);
+            let mut entries = vec![0usize; len];
+            proc.read_buf(chunk, unsafe {
+                std::slice::from_raw_parts_mut(
+                    entries.as_mut_ptr().cast(),
+                    len * size_of::<usize>(),
+                )
+            })?;
+
+            // Slots might be empty.
+            for (i, entry) in entries.into_iter().enumerate().filter(|(_, e)| *e != 0) {
+                pool.insert((first + i) as u32, &self.read_entry(proc, entry)?);
+            }
+        }
+
+        info!("Found {} names", pool.0.len());
+
+        Ok(pool)
+    }
+
+    fn read(&self, proc: &dyn External, table: usize, id: u32) -> Result<String> {
+        let array = proc.read::<usize>(table)?;
+        let entry = self.entry(proc, array, id)?;
+        ensure!(entry != 0, "Name {id:#X} is null");
+
+        self.read_entry(proc, entry)
+    }
+}
@@ -1,17 +1,17 @@
-use crate::{engine::UObjectPtr, State};
+use crate::{engine::UObjectPtr, Dumper};
 use anyhow::Result;
 use log::{info, trace};
 use std::mem::size_of;
 
-const NUM_ELEMENTS_PER_CHUNK: usize = 64 * 1024;
+pub(crate) const NUM_ELEMENTS_PER_CHUNK: usize = 64 * 1024;
 
-pub(crate) fn dump_objects() -> Result<Vec<UObjectPtr>> {
-    let State {
+pub(crate) fn dump_objects(dumper: &Dumper) -> Result<Vec<UObjectPtr>> {
+    let Dumper {
         options,
         external: proc,
         base,
         ..
-    } = State::get();
+    } = dumper;
 
     let [max_elements, num_elements, max_chunks, num_chunks] =
         proc.read::<[u32; 4]>(*base + options.objects + size_of::<usize>() * 2)?;
@@ -20,7 +20,7 @@ pub(crate) fn dump_objects() -> Result<Vec<UObjectPtr>> {
     let array = proc.read::<usize>(*base + options.objects)?;
     let mut objects = vec![];
     for idx in 0..num_elements as usize {
-        if let Some(object) = get_nth_object(array, idx)? {
+        if let Some(object) = get_nth_object(dumper, array, idx)? {
             objects.push(object);
         }
     }
@@ -30,12 +30,12 @@ pub(crate) fn dump_objects() -> Result<Vec<UObjectPtr>> {
     Ok(objects)
 }
 
-fn get_nth_object(array: usize, idx: usize) -> Result<Option<UObjectPtr>> {
-    let State {
+fn get_nth_object(dumper: &Dumper, array: usize, idx: usize) -> Result<Option<UObjectPtr>> {
+    let Dumper {
         external: proc,
         config: offsets,
         ..
-    } = State::get();
+    } = dumper;
 
     let chunk_id = idx / NUM_ELEMENTS_PER_CHUNK;
     let chunk = proc.read::<usize>(array + chunk_id * size_of::<usize>())?;
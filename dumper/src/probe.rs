@@ -0,0 +1,706 @@
+use crate::{
+    config::{
+        NamePoolKind, OfFField, OfFProperty, OfFUObjectItem, OfUEnum, OfUField, OfUFunction,
+        OfUObject, OfUStruct, Offsets, PropertyModel,
+    },
+    discovery::module_sections,
+    engine::FunctionFlags,
+    names::{name_reader, NameReader},
+    objects::NUM_ELEMENTS_PER_CHUNK,
+    Config, External,
+};
+use anyhow::{bail, ensure, Context, Result};
+use log::{debug, info, warn};
+use std::{collections::HashSet, mem::size_of};
+use ucore::FNameEntryHeader;
+
+const STRIDES: &[u32] = &[2, 4, 8];
+const ITEM_SIZES: &[usize] = &[0x10, 0x18, 0x20, 0x28];
+// Number of entries checked when validating layouts of the name pool and object array.
+const SAMPLES: usize = 64;
+// UFunction::Func is located after flags, parameter info and event graph data.
+const FUNCTION_FUNC_DISTANCE: usize = 0x28;
+
+/// Config produced by [`probe_config`].
+#[derive(Debug)]
+pub struct ProbedConfig {
+    pub config: Config,
+    /// Keys of offsets that were guessed and couldn't be checked against the game.
+    pub unverified: Vec<&'static str>,
+}
+
+impl ProbedConfig {
+    /// Same as [`Config::to_toml`], unverified offsets are listed in a leading comment.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut out = String::new();
+        for key in self.unverified.iter() {
+            out += &format!("# {key} is unverified and must be checked manually\n");
+        }
+
+        Ok(out + &self.config.to_toml()?)
+    }
+}
+
+/// Infers layouts of engine structures from the well known objects of `CoreUObject`.
+/// `names` and `objects` are offsets of FNamePool and TUObjectArray relative to `base`,
+/// offsets that can't be inferred (e.g. `ProcessEvent`) are taken from `fallback`.
+pub fn probe_config(
+    proc: &dyn External,
+    base: usize,
+    names: usize,
+    objects: usize,
+    fallback: &Config,
+) -> Result<ProbedConfig> {
+    let pool = base + names;
+    let stride = match fallback.name_pool {
+        NamePoolKind::FNamePool => probe_stride(proc, pool, fallback.fname.header_offset())?,
+        NamePoolKind::TNameEntryArray => fallback.stride,
+    };
+    info!("Probed Stride = {stride}");
+    let reader = name_reader(&Config {
+        stride,
+        ..fallback.clone()
+    })?;
+
+    let (item_size, objects_list) = probe_objects(proc, base + objects)?;
+    info!(
+        "Probed FUObjectItem size = {item_size:#X}, {} objects",
+        objects_list.len()
+    );
+
+    let mut prober = Prober {
+        proc,
+        pool,
+        reader,
+        fname_index: fallback.fname.index,
+        set: objects_list.iter().copied().collect(),
+        objects: objects_list,
+        uobject: None,
+    };
+
+    let uobject = prober.probe_uobject()?;
+    info!("Probed UObject: {uobject:?}");
+    prober.uobject = Some(uobject.clone());
+
+    let property_model = prober.probe_property_model()?;
+    ensure!(
+        property_model == PropertyModel::FField,
+        "Probing UProperty builds is not supported, use one of the configs instead"
+    );
+
+    let ustruct = prober.probe_ustruct()?;
+    info!("Probed UStruct: {ustruct:?}");
+
+    let object_size = prober.struct_size(&ustruct, "Object")?;
+    let ustruct_size = prober.struct_size(&ustruct, "Struct")?;
+    let field_size = prober.struct_size(&ustruct, "Field")?;
+
+    let ffield = prober.probe_ffield(&ustruct)?;
+    info!("Probed FField: {ffield:?}");
+
+    let fproperty = prober.probe_fproperty(&ustruct, &ffield)?;
+    info!("Probed FProperty: {fproperty:?}");
+
+    // Indices of virtual functions and UWorld fields can't be found through reflection.
+    let mut unverified = vec!["ProcessEvent"];
+    if fallback.level_actors.is_some() {
+        unverified.push("LevelActors");
+    }
+
+    let uenum = match prober.probe_uenum(field_size, fallback.fname.pair_size()) {
+        Some(uenum) => uenum,
+        None => {
+            warn!("Failed to probe UEnum, using fallback");
+            unverified.push("UEnum");
+            fallback.uenum.clone()
+        }
+    };
+    info!("Probed UEnum: {uenum:?}");
+
+    let ufunction = OfUFunction {
+        flags: ustruct_size,
+        func: ustruct_size + FUNCTION_FUNC_DISTANCE,
+    };
+    if prober.is_native_func(base, &ufunction) {
+        info!("Verified UFunction::Func = {:#X}", ufunction.func);
+    } else {
+        warn!("Failed to verify UFunction::Func = {:#X}", ufunction.func);
+        unverified.push("UFunction.Func");
+    }
+
+    let config = Config {
+        stride,
+        process_event: fallback.process_event,
+        level_actors: fallback.level_actors,
+        property_model,
+        name_pool: fallback.name_pool,
+        fname: fallback.fname.clone(),
+        fname_entry: fallback.fname_entry.clone(),
+        offsets: Some(Offsets {
+            names: Some(names),
+            objects: Some(objects),
+            world: fallback.offsets.as_ref().and_then(|o| o.world),
+            engine: fallback.offsets.as_ref().and_then(|o| o.engine),
+        }),
+        fuobject_item: OfFUObjectItem {
+            size: item_size,
+            ..fallback.fuobject_item.clone()
+        },
+        uobject,
+        ufield: OfUField { next: object_size },
+        ustruct,
+        uenum,
+        ffield,
+        fproperty,
+        ufunction,
+    };
+
+    Ok(ProbedConfig { config, unverified })
+}
+
+// Walks the first block of the pool and picks the first stride
+// with which every entry looks like a valid name, `header` is the offset of the entry header.
+fn probe_stride(proc: &dyn External, pool: usize, header: usize) -> Result<u32> {
+    let current_block = proc.read::<u32>(pool + size_of::<usize>())?;
+    let cursor = proc.read::<u32>(pool + size_of::<usize>() + size_of::<u32>())? as usize;
+    let block = proc.read::<usize>(pool + size_of::<usize>() + size_of::<u32>() * 2)?;
+
+    let mut data = vec![0; if current_block == 0 { cursor } else { 0x1000 }];
+    proc.read_buf(block, &mut data)?;
+
+    let is_valid = |stride: usize| {
+        let mut offset = 0;
+        for i in 0..SAMPLES {
+            let at = offset + header;
+            if at + size_of::<FNameEntryHeader>() > data.len() {
+                return i > 1;
+            }
+
+            let entry = unsafe {
+                data.as_ptr()
+                    .add(at)
+                    .cast::<FNameEntryHeader>()
+                    .read_unaligned()
+            };
+            let bytes = entry.len() * if entry.is_wide() { 2 } else { 1 };
+            let Some(name) = data.get(at + 2..at + 2 + bytes) else {
+                return i > 1;
+            };
+
+            let printable = if entry.is_wide() {
+                name.chunks(2)
+                    .all(|c| c[1] == 0 && (0x20..0x7F).contains(&c[0]))
+            } else {
+                name.iter().all(|c| (0x20..0x7F).contains(c))
+            };
+            if entry.is_empty() || !printable || (i == 0 && name != b"None") {
+                return false;
+            }
+
+            offset += (header + 2 + bytes).next_multiple_of(stride);
+        }
+
+        true
+    };
+
+    STRIDES
+        .iter()
+        .copied()
+        .find(|stride| is_valid(*stride as usize))
+        .context("Failed to probe FNamePool stride")
+}
+
+fn probe_objects(proc: &dyn External, array: usize) -> Result<(usize, Vec<usize>)> {
+    let chunks = proc.read::<usize>(array)?;
+    let [_, num_elements, _, num_chunks] = proc.read::<[u32; 4]>(array + size_of::<usize>() * 2)?;
+
+    let chunks = (0..num_chunks as usize)
+        .map(|i| proc.read::<usize>(chunks + i * size_of::<usize>()))
+        .collect::<Result<Vec<_>>>()?;
+    let chunk_of = |idx: usize| chunks[idx / NUM_ELEMENTS_PER_CHUNK];
+
+    let samples = (num_elements as usize).min(SAMPLES);
+    let item_size = ITEM_SIZES.iter().copied().find(|size| {
+        let mut seen = HashSet::new();
+        (0..samples).all(|i| {
+            let item = chunk_of(i) + (i % NUM_ELEMENTS_PER_CHUNK) * size;
+            proc.read::<usize>(item).is_ok_and(|ptr| {
+                ptr != 0
+                    && ptr.is_multiple_of(size_of::<usize>())
+                    && seen.insert(ptr)
+                    && proc.read::<usize>(ptr).is_ok()
+            })
+        })
+    });
+    let Some(item_size) = item_size else {
+        bail!("Failed to probe FUObjectItem size")
+    };
+
+    // Slots of destroyed objects are null.
+    let objects = (0..num_elements as usize)
+        .map(|i| {
+            let item = chunk_of(i) + (i % NUM_ELEMENTS_PER_CHUNK) * item_size;
+            proc.read::<usize>(item).unwrap_or_default()
+        })
+        .collect();
+
+    Ok((item_size, objects))
+}
+
+struct Prober<'a> {
+    proc: &'a dyn External,
+    pool: usize,
+    reader: Box<dyn NameReader>,
+    fname_index: usize,
+    objects: Vec<usize>,
+    set: HashSet<usize>,
+    uobject: Option<OfUObject>,
+}
+
+impl Prober<'_> {
+    fn read<T: Copy>(&self, address: usize) -> Option<T> {
+        self.proc.read::<T>(address).ok()
+    }
+
+    fn name_at(&self, address: usize) -> Option<String> {
+        let id = self.read::<u32>(address + self.fname_index)?;
+        self.reader.read(self.proc, self.pool, id).ok()
+    }
+
+    fn object_name(&self, object: usize) -> Option<String> {
+        self.name_at(object + self.uobject.as_ref()?.name)
+    }
+
+    fn class_name(&self, object: usize) -> Option<String> {
+        let class = self.read::<usize>(object + self.uobject.as_ref()?.class)?;
+        self.object_name(class)
+    }
+
+    fn find_object(&self, outer: &str, name: &str) -> Option<usize> {
+        let of = self.uobject.as_ref()?;
+        self.objects.iter().copied().find(|obj| {
+            *obj != 0
+                && self.object_name(*obj).is_some_and(|n| n == name)
+                && self
+                    .read::<usize>(obj + of.outer)
+                    .and_then(|outer| self.object_name(outer))
+                    .is_some_and(|n| n == outer)
+        })
+    }
+
+    fn core(&self, name: &str) -> Result<usize> {
+        self.find_object("/Script/CoreUObject", name)
+            .with_context(|| format!("Failed to find CoreUObject.{name}"))
+    }
+
+    fn struct_size(&self, ustruct: &OfUStruct, name: &str) -> Result<usize> {
+        let size = self.read::<u32>(self.core(name)? + ustruct.props_size);
+        size.map(|s| s as usize)
+            .with_context(|| format!("Failed to read size of CoreUObject.{name}"))
+    }
+
+    fn probe_uobject(&self) -> Result<OfUObject> {
+        let [package, object, ..] = self.objects[..] else {
+            bail!("Object array must contain at least 2 objects")
+        };
+        let samples = &self.objects[..self.objects.len().min(SAMPLES)];
+
+        let index = (0x8..0x40).step_by(4).find(|off| {
+            samples.iter().enumerate().all(|(i, obj)| {
+                *obj == 0 || self.read::<u32>(obj + off).is_some_and(|v| v as usize == i)
+            })
+        });
+        let name = (0x8..0x40).step_by(4).find(|off| {
+            self.name_at(package + off)
+                .is_some_and(|n| n == "/Script/CoreUObject")
+                && self.name_at(object + off).is_some_and(|n| n == "Object")
+        });
+        let (Some(index), Some(name)) = (index, name) else {
+            bail!("Failed to probe UObject index and name")
+        };
+
+        let class = (0x8..0x40).step_by(8).find(|off| {
+            self.read::<usize>(object + off).is_some_and(|class| {
+                self.set.contains(&class)
+                    && self.read::<usize>(class + off) == Some(class)
+                    && self.name_at(class + name).is_some_and(|n| n == "Class")
+            })
+        });
+        let outer = (0x8..0x40).step_by(8).find(|off| {
+            self.read::<usize>(package + off) == Some(0)
+                && self.read::<usize>(object + off) == Some(package)
+        });
+        let (Some(class), Some(outer)) = (class, outer) else {
+            bail!("Failed to probe UObject class and outer")
+        };
+
+        Ok(OfUObject {
+            index,
+            class,
+            name,
+            outer,
+        })
+    }
+
+    // UProperty fields are objects linked into `Children`, FProperty ones aren't objects at all.
+    fn probe_property_model(&self) -> Result<PropertyModel> {
+        let vector = self.core("Vector")?;
+        let uproperty = (0x28..0x80).step_by(8).any(|off| {
+            self.read::<usize>(vector + off).is_some_and(|child| {
+                self.set.contains(&child)
+                    && self.object_name(child).is_some_and(|n| n == "X")
+                    && self
+                        .class_name(child)
+                        .is_some_and(|n| n == "FloatProperty" || n == "DoubleProperty")
+            })
+        });
+
+        Ok(if uproperty {
+            PropertyModel::UProperty
+        } else {
+            PropertyModel::FField
+        })
+    }
+
+    // Func of any native function must point into the code of the module.
+    fn is_native_func(&self, base: usize, ufunction: &OfUFunction) -> bool {
+        let Ok(sections) = module_sections(self.proc, base) else {
+            return false;
+        };
+        let code = sections
+            .iter()
+            .filter(|s| s.is_code())
+            .map(|s| s.address..s.address + s.size)
+            .collect::<Vec<_>>();
+
+        self.objects
+            .iter()
+            .copied()
+            .filter(|o| *o != 0 && self.class_name(*o).is_some_and(|n| n == "Function"))
+            .find(|f| {
+                self.read::<u32>(f + ufunction.flags)
+                    .is_some_and(|flags| flags & FunctionFlags::Native.bits() != 0)
+            })
+            .and_then(|f| self.read::<usize>(f + ufunction.func))
+            .is_some_and(|func| code.iter().any(|c| c.contains(&func)))
+    }
+
+    fn probe_ustruct(&self) -> Result<OfUStruct> {
+        let object = self.core("Object")?;
+        let field = self.core("Field")?;
+        let ustruct = self.core("Struct")?;
+        let vector = self.core("Vector")?;
+
+        let super_struct = (0x28..0x80).step_by(8).find(|off| {
+            self.read::<usize>(ustruct + off) == Some(field)
+                && self.read::<usize>(field + off) == Some(object)
+        });
+        // UObject and UField only have a vtable, object fields and the next pointer.
+        let props_size = (0x28..0x100).step_by(4).find(|off| {
+            self.read::<u32>(object + off) == Some(0x28)
+                && self.read::<u32>(field + off) == Some(0x30)
+        });
+        // Vector has at least one property while Object has none.
+        let children_props = (0x28..0x80).step_by(8).find(|off| {
+            self.read::<usize>(object + off) == Some(0)
+                && self
+                    .read::<usize>(vector + off)
+                    .is_some_and(|child| child != 0 && !self.set.contains(&child))
+                && (0x8..0x40)
+                    .step_by(4)
+                    .any(|name| self.field_name_at(vector, *off, name, "X"))
+        });
+        let (Some(super_struct), Some(props_size), Some(children_props)) =
+            (super_struct, props_size, children_props)
+        else {
+            bail!("Failed to probe UStruct")
+        };
+
+        // Functions are linked into children of their classes.
+        let classes = self
+            .objects
+            .iter()
+            .copied()
+            .filter(|o| *o != 0 && self.class_name(*o).is_some_and(|n| n == "Class"))
+            .collect::<Vec<_>>();
+        let children = (0x28..0x80)
+            .step_by(8)
+            .filter(|off| *off != super_struct && *off != children_props)
+            .find(|off| {
+                classes.iter().any(|class| {
+                    self.read::<usize>(class + off).is_some_and(|child| {
+                        self.set.contains(&child)
+                            && self.class_name(child).is_some_and(|n| n == "Function")
+                    })
+                })
+            });
+        if children.is_none() {
+            warn!("Failed to probe UStruct children");
+        }
+
+        Ok(OfUStruct {
+            super_struct,
+            children,
+            children_props,
+            props_size,
+        })
+    }
+
+    fn field_name_at(&self, owner: usize, children: usize, name: usize, expected: &str) -> bool {
+        self.read::<usize>(owner + children)
+            .and_then(|child| self.name_at(child + name))
+            .is_some_and(|n| n == expected)
+    }
+
+    fn probe_ffield(&self, ustruct: &OfUStruct) -> Result<OfFField> {
+        let vector = self.core("Vector")?;
+        let x = self
+            .read::<usize>(vector + ustruct.children_props)
+            .context("Failed to read Vector properties")?;
+
+        let name = (0x8..0x40)
+            .step_by(4)
+            .find(|off| self.field_name_at(vector, ustruct.children_props, *off, "X"));
+        let Some(name) = name else {
+            bail!("Failed to probe FField name")
+        };
+
+        let next = (0x8..0x40).step_by(8).find(|off| {
+            self.read::<usize>(x + off)
+                .and_then(|y| self.name_at(y + name))
+                .is_some_and(|n| n == "Y")
+        });
+        // FFieldClass starts with its name.
+        let class = (0x8..0x40).step_by(8).find(|off| {
+            self.read::<usize>(x + off)
+                .and_then(|class| self.name_at(class))
+                .is_some_and(|n| n == "FloatProperty" || n == "DoubleProperty")
+        });
+        let (Some(class), Some(next)) = (class, next) else {
+            bail!("Failed to probe FField class and next")
+        };
+
+        Ok(OfFField { class, next, name })
+    }
+
+    fn probe_fproperty(&self, ustruct: &OfUStruct, ffield: &OfFField) -> Result<OfFProperty> {
+        let vector = self.core("Vector")?;
+        let mut axes = vec![];
+        let mut field = self.read::<usize>(vector + ustruct.children_props);
+        while let Some(ptr) = field.filter(|f| *f != 0 && axes.len() < 3) {
+            axes.push(ptr);
+            field = self.read::<usize>(ptr + ffield.next);
+        }
+        if axes.len() != 3 {
+            bail!("Vector must have 3 properties")
+        }
+
+        let values = |off: usize| -> Option<Vec<u32>> {
+            axes.iter().map(|a| self.read::<u32>(a + off)).collect()
+        };
+
+        // Axes are either floats or doubles, ArrayDim and PropertyFlags surround ElementSize.
+        let element_size = (0x30..0x80).step_by(4).find(|off| {
+            values(*off).is_some_and(|v| v.iter().all(|s| *s == v[0] && (s == &4 || s == &8)))
+                && values(off - 4).is_some_and(|v| v.iter().all(|d| *d == 1))
+        });
+        let Some(element_size) = element_size else {
+            bail!("Failed to probe FProperty element size")
+        };
+        let elem = values(element_size).unwrap()[0];
+
+        let offset = (element_size + 8..0x80)
+            .step_by(4)
+            .find(|off| values(*off).is_some_and(|v| v == [0, elem, elem * 2]));
+        let Some(offset) = offset else {
+            bail!("Failed to probe FProperty offset")
+        };
+
+        let size = self.probe_fproperty_size(ustruct, ffield, offset)?;
+
+        Ok(OfFProperty {
+            array_dim: element_size - 4,
+            element_size,
+            flags: element_size + 4,
+            offset,
+            size,
+        })
+    }
+
+    // Struct and object properties are followed by a pointer to the referenced struct.
+    fn probe_fproperty_size(
+        &self,
+        ustruct: &OfUStruct,
+        ffield: &OfFField,
+        offset: usize,
+    ) -> Result<usize> {
+        let first = (offset + size_of::<u32>()).next_multiple_of(size_of::<usize>());
+
+        for owner in self.objects.iter().copied().filter(|o| *o != 0) {
+            if !self
+                .class_name(owner)
+                .is_some_and(|n| n == "ScriptStruct" || n == "Class")
+            {
+                continue;
+            }
+
+            let mut field = self.read::<usize>(owner + ustruct.children_props);
+            while let Some(ptr) = field.filter(|f| *f != 0) {
+                let class = self
+                    .read::<usize>(ptr + ffield.class)
+                    .and_then(|c| self.name_at(c));
+                let expected = match class.as_deref() {
+                    Some("StructProperty") => "ScriptStruct",
+                    Some("ObjectProperty") => "Class",
+                    _ => {
+                        field = self.read::<usize>(ptr + ffield.next);
+                        continue;
+                    }
+                };
+
+                let size = (first..0x100).step_by(8).find(|off| {
+                    self.read::<usize>(ptr + off).is_some_and(|target| {
+                        self.set.contains(&target)
+                            && self.class_name(target).is_some_and(|n| n == expected)
+                    })
+                });
+                if let Some(size) = size {
+                    debug!("Probed FProperty size using {class:?} at {ptr:#X}");
+                    return Ok(size);
+                }
+
+                field = self.read::<usize>(ptr + ffield.next);
+            }
+        }
+
+        bail!("Failed to probe FProperty size")
+    }
+
+    fn probe_uenum(&self, field_size: usize, pair_size: usize) -> Option<OfUEnum> {
+        let enums = self
+            .objects
+            .iter()
+            .copied()
+            .filter(|o| *o != 0 && self.class_name(*o).is_some_and(|n| n == "Enum"));
+
+        // Names is a TArray<TPair<FName, int64>>, the first one that holds valid names wins.
+        for uenum in enums {
+            let names = (field_size..field_size + 0x40).step_by(8).find(|off| {
+                let Some(data) = self.read::<usize>(uenum + off).filter(|d| *d != 0) else {
+                    return false;
+                };
+                let Some([num, max]) = self.read::<[u32; 2]>(uenum + off + size_of::<usize>())
+                else {
+                    return false;
+                };
+
+                num > 0
+                    && num <= max
+                    && (0..num.min(16) as usize).all(|i| {
+                        self.name_at(data + i * pair_size)
+                            .is_some_and(|n| !n.is_empty())
+                    })
+            });
+
+            if let Some(names) = names {
+                return Some(OfUEnum { names });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::probe_config;
+    use crate::{
+        config::{OfFField, OfFProperty, OfFUObjectItem, OfUEnum, OfUFunction, OfUStruct, Offsets},
+        engine::FunctionFlags,
+        fixture::{Image, Prop},
+        Config,
+    };
+
+    fn actor_image(config: Config) -> Image {
+        let mut image = Image::new(config);
+        let engine = image.package("/Script/Engine");
+        image.enumeration(engine, "ENetRole", &[("ROLE_None", 0)]);
+        let actor = image.class(engine, "Actor", image.core.object, 0x30);
+        image.property(actor, "Instigator", Prop::Object(actor), 0x28);
+        image.function(actor, "K2_DestroyActor", FunctionFlags::Native);
+
+        image
+    }
+
+    #[test]
+    fn test_probe_config() {
+        let defaults = Config::default();
+        let expected = Config {
+            stride: 4,
+            offsets: Some(Offsets {
+                names: Some(0x2100),
+                objects: Some(0x2200),
+                world: None,
+                engine: None,
+            }),
+            fuobject_item: OfFUObjectItem {
+                size: 0x20,
+                ..defaults.fuobject_item.clone()
+            },
+            ustruct: OfUStruct {
+                super_struct: 0x48,
+                children: Some(0x50),
+                children_props: 0x58,
+                props_size: 0x60,
+            },
+            uenum: OfUEnum { names: 0x48 },
+            ffield: OfFField {
+                class: 0x8,
+                next: 0x18,
+                name: 0x20,
+            },
+            fproperty: OfFProperty {
+                array_dim: 0x40,
+                element_size: 0x44,
+                flags: 0x48,
+                offset: 0x54,
+                size: 0x80,
+            },
+            ..defaults.clone()
+        };
+
+        let image = actor_image(expected.clone());
+        let (base, names, objects) = (image.base(), image.names_offset(), image.objects_offset());
+        let probed = probe_config(&image.finish(), base, names, objects, &defaults).unwrap();
+        assert_eq!(probed.config, expected);
+        assert_eq!(probed.unverified, ["ProcessEvent"]);
+
+        let parsed: Config = toml::from_str(&probed.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed, expected);
+
+        // Func that doesn't point into the code is reported in the output.
+        let image = actor_image(Config {
+            ufunction: OfUFunction {
+                flags: 0xB0,
+                func: 0xE0,
+            },
+            ..expected
+        });
+        let probed = probe_config(&image.finish(), base, names, objects, &defaults).unwrap();
+        assert_eq!(probed.unverified, ["ProcessEvent", "UFunction.Func"]);
+        assert!(probed
+            .to_toml()
+            .unwrap()
+            .contains("\n# UFunction.Func is unverified"));
+    }
+
+    #[test]
+    fn test_probe_uproperty() {
+        let config: Config = toml::from_str(include_str!("../../configs/4_22.toml")).unwrap();
+        let image = actor_image(config.clone());
+        let (base, names, objects) = (image.base(), image.names_offset(), image.objects_offset());
+
+        let err = probe_config(&image.finish(), base, names, objects, &config).unwrap_err();
+        assert!(err.to_string().contains("UProperty"));
+    }
+}
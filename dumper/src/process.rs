@@ -1,16 +1,17 @@
 use crate::{
+    config::Offsets,
     cycles,
     engine::{
-        FBoolProperty, FFieldPtr, FPropertyPtr, UClassPtr, UEnumPtr, UFunctionPtr, UObjectPtr,
-        UStructPtr,
+        FBoolProperty, FFieldClassPtr, FFieldPtr, FPropertyPtr, UClassPtr, UEnumPtr, UFunctionPtr,
+        UObjectPtr, UStructPtr,
     },
     sdk::{Enum, Field, FieldOptions, Function, FunctionArg, Object, PropertyKind, Sdk, Struct},
     utils::{sanitize_ident, strip_package_name, AccumulatorResult, BitfieldAccumulator, Layout},
-    State,
+    Dumper,
 };
 use anyhow::{bail, Result};
 use indicatif::ProgressBar;
-use log::info;
+use log::{info, warn};
 use petgraph::graph::NodeIndex;
 use std::{
     collections::{HashMap, HashSet},
@@ -21,8 +22,15 @@ use std::{
 };
 use ucore::{fqn, Fqn};
 
-pub(crate) fn process(objects: &[UObjectPtr]) -> Result<Sdk> {
-    let mut sdk = Sdk::default();
+pub(crate) fn process(dumper: &Dumper, objects: &[UObjectPtr]) -> Result<Sdk> {
+    let mut sdk = Sdk {
+        config: dumper.config.clone(),
+        ..Default::default()
+    };
+    // Discovered offsets are kept too, generated SDKs use them to find the globals.
+    let offsets = sdk.config.offsets.get_or_insert_with(Offsets::default);
+    offsets.names = Some(dumper.options.names);
+    offsets.objects = Some(dumper.options.objects);
     let mut foreign_map: HashMap<NodeIndex, HashSet<Fqn>> = HashMap::new();
 
     let start = Instant::now();
@@ -30,24 +38,24 @@ pub(crate) fn process(objects: &[UObjectPtr]) -> Result<Sdk> {
     let mut functions = vec![];
     let progress = ProgressBar::new(objects.len() as _);
     for object in objects.iter() {
-        let Some(outer) = get_outermost_object(*object)? else {
+        let Some(outer) = get_outermost_object(dumper, *object)? else {
             continue;
         };
 
-        assert!(!outer.is_null() && outer.outer()?.is_null());
-        let outer_name = sanitize_ident(strip_package_name(outer.name().get()?));
+        assert!(!outer.is_null() && outer.outer(dumper)?.is_null());
+        let outer_name = package_ident(dumper, outer)?;
 
-        let object = if object.is_a(fqn!(CoreUObject.Enum))? {
+        let object = if object.is_a(dumper, fqn!(CoreUObject.Enum))? {
             progress.inc(1);
-            Object::Enum(index_enum(object.cast())?)
-        } else if object.is_a(fqn!(CoreUObject.ScriptStruct))?
-            || object.is_a(fqn!(CoreUObject.Class))?
+            Object::Enum(index_enum(dumper, object.cast())?)
+        } else if object.is_a(dumper, fqn!(CoreUObject.ScriptStruct))?
+            || object.is_a(dumper, fqn!(CoreUObject.Class))?
         {
             let key = sdk.retrieve_key(&outer_name);
             let foreign = foreign_map.entry(key).or_default();
             progress.inc(1);
-            Object::Struct(index_struct(object.cast(), foreign)?)
-        } else if object.is_a(fqn!(CoreUObject.Function))? {
+            Object::Struct(index_struct(dumper, object.cast(), foreign)?)
+        } else if object.is_a(dumper, fqn!(CoreUObject.Function))? {
             functions.push(object);
             progress.inc(1);
             continue;
@@ -63,24 +71,26 @@ pub(crate) fn process(objects: &[UObjectPtr]) -> Result<Sdk> {
     // Functions are processed after all structures in order to avoid issues
     // when functions go before the structure the belong in.
     for &object in functions.iter() {
-        let Some(outer) = get_outermost_object(*object)? else {
+        let Some(outer) = get_outermost_object(dumper, *object)? else {
             continue;
         };
 
-        if object.is_a(fqn!(CoreUObject.Function))? {
-            assert!(!outer.is_null() && outer.outer()?.is_null());
-            let outer_name = sanitize_ident(strip_package_name(outer.name().get()?));
+        if object.is_a(dumper, fqn!(CoreUObject.Function))? {
+            assert!(!outer.is_null() && outer.outer(dumper)?.is_null());
+            let outer_name = package_ident(dumper, outer)?;
 
             let key = sdk.retrieve_key(&outer_name);
             let foreign = foreign_map.entry(key).or_default();
-            let Ok(target) = object.outer()?.fqn() else {
+            let Ok(target) = object.outer(dumper)?.fqn(dumper) else {
+                continue;
+            };
+            // Delegate signatures can be declared directly in the package.
+            let Some(target) = sdk.owned.get(&target) else {
                 continue;
             };
 
-            let function = index_function(*object, foreign)?;
-            let (Object::Class(target) | Object::Struct(target)) =
-                &*sdk.owned.get(&target).unwrap().ptr
-            else {
+            let function = index_function(dumper, *object, foreign)?;
+            let (Object::Class(target) | Object::Struct(target)) = &*target.ptr else {
                 // Functions will be only in classes or structs.
                 unreachable!()
             };
@@ -95,7 +105,7 @@ pub(crate) fn process(objects: &[UObjectPtr]) -> Result<Sdk> {
     populate_dependency_map(&mut sdk, foreign_map);
     shrink_base_classes(&sdk);
 
-    if !State::get().options.allow_cycles {
+    if !dumper.options.allow_cycles {
         cycles::eliminate_dependency_cycles(&mut sdk);
     }
 
@@ -135,37 +145,52 @@ fn populate_dependency_map(sdk: &mut Sdk, foreign_map: HashMap<NodeIndex, HashSe
     }
 }
 
-fn get_outermost_object(object: UObjectPtr) -> Result<Option<UObjectPtr>> {
-    Ok(successors(object.outer()?.non_null(), |obj| {
-        obj.outer().unwrap().non_null()
+fn get_outermost_object(dumper: &Dumper, object: UObjectPtr) -> Result<Option<UObjectPtr>> {
+    Ok(successors(object.outer(dumper)?.non_null(), |obj| {
+        obj.outer(dumper).unwrap().non_null()
     })
     .last())
 }
 
-fn index_function(object: UObjectPtr, foreign: &mut HashSet<Fqn>) -> Result<Function> {
-    let ident = sanitize_ident(object.name().get()?).into_owned();
-    let index = object.index()?;
+/// Identifier of the package after applying merge options.
+fn package_ident(dumper: &Dumper, package: UObjectPtr) -> Result<String> {
+    let ident = sanitize_ident(strip_package_name(package.name(dumper).get(dumper)?));
+    let merged = dumper
+        .options
+        .merge
+        .get(&*ident)
+        .cloned()
+        .unwrap_or_else(|| ident.into_owned());
+
+    Ok(merged)
+}
+
+fn index_function(
+    dumper: &Dumper,
+    object: UObjectPtr,
+    foreign: &mut HashSet<Fqn>,
+) -> Result<Function> {
+    let ident = sanitize_ident(object.name(dumper).get(dumper)?).into_owned();
+    let index = object.index(dumper)?;
 
-    let flags = object.cast::<UFunctionPtr>().flags()?;
+    let flags = object.cast::<UFunctionPtr>().flags(dumper)?;
 
     let mut args = vec![];
 
     let ptr = object.cast::<UStructPtr>();
-    for arg in successors(ptr.children_props()?.non_null(), |field| {
-        field.next().unwrap().non_null()
-    }) {
+    for arg in ptr.properties(dumper)? {
         let property = arg.cast::<FPropertyPtr>();
 
-        let name = sanitize_ident(arg.name().get()?).into_owned();
-        let kind = get_property_kind(arg, foreign)?;
-        let flags = property.flags()?;
+        let name = sanitize_ident(arg.name(dumper).get(dumper)?).into_owned();
+        let kind = get_property_kind(dumper, arg, foreign)?;
+        let flags = property.flags(dumper)?;
 
         let arg = FunctionArg { name, kind, flags };
         args.push(arg);
     }
 
     let function = Function {
-        fqn: object.fqn()?,
+        fqn: object.fqn(dumper)?,
         ident,
         index,
         flags,
@@ -174,15 +199,14 @@ fn index_function(object: UObjectPtr, foreign: &mut HashSet<Fqn>) -> Result<Func
     Ok(function)
 }
 
-fn index_enum(uenum_ptr: UEnumPtr) -> Result<Enum> {
-    let fqn = uenum_ptr.cast::<UObjectPtr>().fqn()?;
+fn index_enum(dumper: &Dumper, uenum_ptr: UEnumPtr) -> Result<Enum> {
+    let fqn = uenum_ptr.cast::<UObjectPtr>().fqn(dumper)?;
 
     let variants = uenum_ptr
-        .names()?
-        .iter::<(u64, i64)>()
-        .flatten()
+        .variants(dumper)?
+        .into_iter()
         .map(|(n, v)| {
-            let name = State::get().get_name(n as u32)?;
+            let name = dumper.get_name(n)?;
             let ident =
                 sanitize_ident(name.split_once("::").map(|v| v.1).unwrap_or(name)).into_owned();
             Result::Ok((ident, v))
@@ -221,10 +245,12 @@ fn pick_enum_size(range: impl Iterator<Item = i64>) -> usize {
     }
 }
 
-fn select_prefix(ustruct: UStructPtr) -> Result<char> {
+fn select_prefix(dumper: &Dumper, ustruct: UStructPtr) -> Result<char> {
     let child_of = |fqn: Fqn| {
-        successors(ustruct.non_null(), |s| s.super_struct().unwrap().non_null())
-            .any(|s| s.cast::<UObjectPtr>().fqn().unwrap() == fqn)
+        successors(ustruct.non_null(), |s| {
+            s.super_struct(dumper).unwrap().non_null()
+        })
+        .any(|s| s.cast::<UObjectPtr>().fqn(dumper).unwrap() == fqn)
     };
 
     let prefix = if child_of(fqn!(Engine.Actor)) {
@@ -238,21 +264,25 @@ fn select_prefix(ustruct: UStructPtr) -> Result<char> {
     Ok(prefix)
 }
 
-fn index_struct(ustruct_ptr: UStructPtr, foreign: &mut HashSet<Fqn>) -> Result<Struct> {
-    let config = &State::get().config;
-    let fqn = ustruct_ptr.cast::<UObjectPtr>().fqn()?;
+fn index_struct(
+    dumper: &Dumper,
+    ustruct_ptr: UStructPtr,
+    foreign: &mut HashSet<Fqn>,
+) -> Result<Struct> {
+    let config = &dumper.config;
+    let fqn = ustruct_ptr.cast::<UObjectPtr>().fqn(dumper)?;
 
-    let prefix = select_prefix(ustruct_ptr.cast())?;
+    let prefix = select_prefix(dumper, ustruct_ptr.cast())?;
     let ident = format!("{prefix}{}", sanitize_ident(fqn.name()));
-    let index = ustruct_ptr.cast::<UObjectPtr>().index()?;
+    let index = ustruct_ptr.cast::<UObjectPtr>().index(dumper)?;
 
-    let size = ustruct_ptr.props_size()? as usize;
-    let align = ustruct_ptr.min_align()? as usize;
+    let size = ustruct_ptr.props_size(dumper)? as usize;
+    let align = ustruct_ptr.min_align(dumper)? as usize;
 
     let parent = ustruct_ptr
-        .super_struct()?
+        .super_struct(dumper)?
         .non_null()
-        .map(|s| s.cast::<UObjectPtr>().fqn())
+        .map(|s| s.cast::<UObjectPtr>().fqn(dumper))
         .transpose()?;
     if let Some(fqn) = parent {
         foreign.insert(fqn);
@@ -261,7 +291,7 @@ fn index_struct(ustruct_ptr: UStructPtr, foreign: &mut HashSet<Fqn>) -> Result<S
     let mut ustruct = Struct {
         is_uobject: !ustruct_ptr
             .cast::<UObjectPtr>()
-            .is_a(fqn!(CoreUObject.ScriptStruct))?,
+            .is_a(dumper, fqn!(CoreUObject.ScriptStruct))?,
         layout: Layout { size, align },
         functions: vec![].into(),
         shrink: None.into(),
@@ -288,24 +318,22 @@ fn index_struct(ustruct_ptr: UStructPtr, foreign: &mut HashSet<Fqn>) -> Result<S
         })
     }
 
-    for field in successors(ustruct_ptr.children_props()?.non_null(), |field| {
-        field.next().unwrap().non_null()
-    }) {
+    for field in ustruct_ptr.properties(dumper)? {
         let fproperty = field.cast::<FPropertyPtr>();
 
-        let name = sanitize_ident(field.name().get()?).into_owned();
-        let kind = get_property_kind(field, foreign)?;
-        let offset = fproperty.offset()? as usize;
-        let array_dim = fproperty.array_dim()? as usize;
-        let elem_size = fproperty.element_size()? as usize;
+        let name = sanitize_ident(field.name(dumper).get(dumper)?).into_owned();
+        let kind = get_property_kind(dumper, field, foreign)?;
+        let offset = fproperty.offset(dumper)? as usize;
+        let array_dim = fproperty.array_dim(dumper)? as usize;
+        let elem_size = fproperty.element_size(dumper)? as usize;
 
         if matches!(kind, PropertyKind::Bool) {
-            let vars = fproperty.cast::<FBoolProperty>().vars()?;
+            let vars = fproperty.cast::<FBoolProperty>().vars(dumper)?;
             assert_eq!(vars.byte_offset, 0);
             assert_eq!(vars.field_size, 1);
         }
 
-        let acc_result = accumulator.accumulate(&name, fproperty, &kind, offset)?;
+        let acc_result = accumulator.accumulate(dumper, &name, fproperty, &kind, offset)?;
         match acc_result {
             AccumulatorResult::Skip => continue,
             AccumulatorResult::Append(groups) => {
@@ -332,15 +360,60 @@ fn index_struct(ustruct_ptr: UStructPtr, foreign: &mut HashSet<Fqn>) -> Result<S
     Ok(ustruct)
 }
 
-fn get_property_kind(field: FFieldPtr, foreign: &mut HashSet<Fqn>) -> Result<PropertyKind> {
-    let State {
+/// Reads class pointer that is stored right after the base property.
+fn property_class(
+    dumper: &Dumper,
+    field: FFieldPtr,
+    offset: usize,
+    foreign: &mut HashSet<Fqn>,
+) -> Result<Fqn> {
+    let uclass = dumper
+        .external
+        .read::<UClassPtr>(field.0 + dumper.config.fproperty.size + offset)?;
+    let fqn = uclass.cast::<UObjectPtr>().fqn(dumper)?;
+    foreign.insert(fqn);
+
+    Ok(fqn)
+}
+
+/// Reads signature function of a delegate property, `None` if the property has none.
+fn property_signature(
+    dumper: &Dumper,
+    field: FFieldPtr,
+    foreign: &mut HashSet<Fqn>,
+) -> Result<Option<Fqn>> {
+    let function = dumper
+        .external
+        .read::<UObjectPtr>(field.0 + dumper.config.fproperty.size)?;
+    if function.is_null() {
+        let name = field.name(dumper).get(dumper)?;
+        warn!("Delegate property {name} has no signature function");
+        return Ok(None);
+    }
+    let fqn = function.fqn(dumper)?;
+
+    // Signatures declared outside of classes belong to the package itself.
+    let owner = function.outer(dumper)?;
+    if !owner.outer(dumper)?.is_null() {
+        foreign.insert(owner.fqn(dumper)?);
+    }
+
+    Ok(Some(fqn))
+}
+
+fn get_property_kind(
+    dumper: &Dumper,
+    field: FFieldPtr,
+    foreign: &mut HashSet<Fqn>,
+) -> Result<PropertyKind> {
+    let Dumper {
         external: proc,
         config: offsets,
         ..
-    } = State::get();
+    } = dumper;
 
     let property = field.cast::<FPropertyPtr>();
-    let classname = field.class()?.name().get()?;
+    let classname = field.class(dumper)?.name(dumper).get(dumper)?;
     let kind = match classname {
         "BoolProperty" => PropertyKind::Bool,
         "NameProperty" => PropertyKind::Name,
@@ -360,16 +433,20 @@ fn get_property_kind(field: FFieldPtr, foreign: &mut HashSet<Fqn>) -> Result<Pro
         "UInt32Property" => PropertyKind::UInt32,
         "UInt64Property" => PropertyKind::UInt64,
 
-        "ClassProperty" | "ObjectProperty" => {
-            let uclass = proc.read::<UClassPtr>(field.0 + offsets.fproperty.size)?;
-            let fqn = uclass.cast::<UObjectPtr>().fqn()?;
-            foreign.insert(fqn);
-
-            PropertyKind::Ptr(fqn)
+        "ClassProperty" | "ClassPtrProperty" | "ObjectProperty" => {
+            PropertyKind::Ptr(property_class(dumper, field, 0, foreign)?)
+        }
+        "WeakObjectProperty" => PropertyKind::Weak(property_class(dumper, field, 0, foreign)?),
+        "SoftObjectProperty" => PropertyKind::Soft(property_class(dumper, field, 0, foreign)?),
+        "LazyObjectProperty" => PropertyKind::Lazy(property_class(dumper, field, 0, foreign)?),
+        "InterfaceProperty" => PropertyKind::Interface(property_class(dumper, field, 0, foreign)?),
+        // MetaClass follows PropertyClass.
+        "SoftClassProperty" => {
+            PropertyKind::SoftClass(property_class(dumper, field, size_of::<usize>(), foreign)?)
         }
         "StructProperty" => {
             let ustruct = proc.read::<UStructPtr>(field.0 + offsets.fproperty.size)?;
-            let fqn = ustruct.cast::<UObjectPtr>().fqn()?;
+            let fqn = ustruct.cast::<UObjectPtr>().fqn(dumper)?;
             foreign.insert(fqn);
 
             PropertyKind::Inline(fqn)
@@ -377,18 +454,18 @@ fn get_property_kind(field: FFieldPtr, foreign: &mut HashSet<Fqn>) -> Result<Pro
         "EnumProperty" => {
             let uenum =
                 proc.read::<UEnumPtr>(field.0 + offsets.fproperty.size + size_of::<usize>())?;
-            let fqn = uenum.cast::<UObjectPtr>().fqn()?;
+            let fqn = uenum.cast::<UObjectPtr>().fqn(dumper)?;
             foreign.insert(fqn);
 
             PropertyKind::Inline(fqn)
         }
         "ArrayProperty" => {
             let inner = proc.read::<FPropertyPtr>(field.0 + offsets.fproperty.size)?;
-            PropertyKind::Vec(get_property_kind(inner.cast(), foreign)?.into())
+            PropertyKind::Vec(get_property_kind(dumper, inner.cast(), foreign)?.into())
         }
         "SetProperty" => {
             let inner = proc.read::<FPropertyPtr>(field.0 + offsets.fproperty.size)?;
-            PropertyKind::Set(get_property_kind(inner.cast(), foreign)?.into())
+            PropertyKind::Set(get_property_kind(dumper, inner.cast(), foreign)?.into())
         }
         "MapProperty" => {
             let key = proc.read::<FPropertyPtr>(field.0 + offsets.fproperty.size)?;
@@ -396,24 +473,26 @@ fn get_property_kind(field: FFieldPtr, foreign: &mut HashSet<Fqn>) -> Result<Pro
                 proc.read::<FPropertyPtr>(field.0 + offsets.fproperty.size + size_of::<usize>())?;
 
             PropertyKind::Map {
-                key: get_property_kind(key.cast(), foreign)?.into(),
-                value: get_property_kind(value.cast(), foreign)?.into(),
+                key: get_property_kind(dumper, key.cast(), foreign)?.into(),
+                value: get_property_kind(dumper, value.cast(), foreign)?.into(),
             }
         }
-        "ClassPtrProperty"
-        | "DelegateProperty"
-        | "FieldPathProperty"
-        | "InterfaceProperty"
-        | "LazyObjectProperty"
-        | "SoftClassProperty"
-        | "SoftObjectProperty"
-        | "WeakObjectProperty"
-        | "MulticastInlineDelegateProperty"
-        | "MulticastSparseDelegateProperty" => PropertyKind::Unknown,
+        "DelegateProperty" => property_signature(dumper, field, foreign)?
+            .map_or(PropertyKind::Unknown, PropertyKind::Delegate),
+        "MulticastDelegateProperty" | "MulticastInlineDelegateProperty" => {
+            property_signature(dumper, field, foreign)?
+                .map_or(PropertyKind::Unknown, PropertyKind::MulticastDelegate)
+        }
+        "MulticastSparseDelegateProperty" => property_signature(dumper, field, foreign)?
+            .map_or(PropertyKind::Unknown, PropertyKind::SparseDelegate),
+        "FieldPathProperty" => {
+            let class = proc.read::<FFieldClassPtr>(field.0 + offsets.fproperty.size)?;
+            PropertyKind::FieldPath(class.name(dumper).get(dumper)?.to_owned())
+        }
         other => bail!("Unrecogninzed property classname {other}"),
     };
 
-    let array_dim = property.array_dim()? as usize;
+    let array_dim = property.array_dim(dumper)? as usize;
 
     if array_dim != 1 {
         assert!(!matches!(kind, PropertyKind::Unknown));
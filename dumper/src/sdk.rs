@@ -1,13 +1,19 @@
 use crate::{
     engine::{FunctionFlags, PropertyFlags},
+    names::intern,
     utils::{BitfieldGroup, Layout},
-    State,
+    Config,
 };
-use petgraph::{graph::NodeIndex, stable_graph::StableGraph, Directed};
+use anyhow::{bail, Context, Result};
+use petgraph::{graph::NodeIndex, stable_graph::StableGraph, Directed, Direction::Outgoing};
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
-    cell::{Cell, RefCell},
+    cell::{Cell, Ref, RefCell},
     collections::HashMap,
-    fmt::{self, Debug},
+    fmt::{self, Debug, Display},
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
     rc::Rc,
 };
 use ucore::Fqn;
@@ -22,6 +28,8 @@ pub struct Sdk {
     pub packages: StableGraph<Package, (), Directed>,
     pub indices: HashMap<Rc<str>, NodeIndex>,
     pub owned: HashMap<Fqn, ObjectInfo>,
+    /// Config the SDK was dumped with.
+    pub config: Config,
 }
 
 pub struct Package {
@@ -29,6 +37,91 @@ pub struct Package {
     pub objects: Vec<Rc<Object>>,
 }
 
+/// Packages are written with the idents of the packages they depend on.
+impl Serialize for Sdk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct PackageRef<'a> {
+            ident: &'a str,
+            dependencies: Vec<&'a str>,
+            objects: Vec<&'a Object>,
+        }
+
+        let packages = self
+            .packages
+            .node_indices()
+            .map(|idx| {
+                let mut dependencies = self
+                    .packages
+                    .neighbors_directed(idx, Outgoing)
+                    .map(|dep| &*self.packages[dep].ident)
+                    .collect::<Vec<_>>();
+                dependencies.sort_unstable();
+
+                let pkg = &self.packages[idx];
+                PackageRef {
+                    ident: &pkg.ident,
+                    dependencies,
+                    objects: pkg.objects.iter().map(|o| &**o).collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut state = serializer.serialize_struct("Sdk", 2)?;
+        state.serialize_field("config", &self.config)?;
+        state.serialize_field("packages", &packages)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct SdkModel {
+    config: Config,
+    packages: Vec<PackageModel>,
+}
+
+#[derive(Deserialize)]
+struct PackageModel {
+    ident: String,
+    dependencies: Vec<String>,
+    objects: Vec<Object>,
+}
+
+/// Stores [`Fqn`] as a `Package.Name` string.
+mod serde_fqn {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(fqn: &Fqn, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(fqn)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fqn, D::Error> {
+        let fqn = String::deserialize(deserializer)?;
+        Ok(Fqn::from_human_readable(intern(&fqn)))
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            fqn: &Option<Fqn>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match fqn {
+                Some(fqn) => serializer.collect_str(fqn),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Fqn>, D::Error> {
+            let fqn = Option::<String>::deserialize(deserializer)?;
+            Ok(fqn.map(|fqn| Fqn::from_human_readable(intern(&fqn))))
+        }
+    }
+}
+
 impl Debug for Package {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.ident)
@@ -49,7 +142,7 @@ pub struct ObjectInfo {
     pub ptr: Rc<Object>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Object {
     Enum(Enum),
     Struct(Struct),
@@ -80,22 +173,98 @@ impl Object {
 }
 
 impl Sdk {
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Rebuilds the SDK saved with [`Sdk::save_json`].
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open SDK model {}", path.display()))?;
+        let model: SdkModel = serde_json::from_reader(BufReader::new(file))?;
+
+        let mut sdk = Sdk {
+            config: model.config,
+            ..Default::default()
+        };
+
+        for pkg in model.packages.iter() {
+            sdk.retrieve_key(&pkg.ident);
+        }
+
+        for pkg in model.packages {
+            let idx = sdk.retrieve_key(&pkg.ident);
+            for dep in pkg.dependencies.iter() {
+                let dep = *sdk
+                    .indices
+                    .get(dep.as_str())
+                    .with_context(|| format!("Unknown dependency {dep} of {}", pkg.ident))?;
+                sdk.packages.update_edge(idx, dep, ());
+            }
+
+            for object in pkg.objects {
+                sdk.add(&pkg.ident, object);
+            }
+        }
+
+        // Codegen expects every referenced object to be present.
+        for info in sdk.owned.values() {
+            let (Object::Class(ustruct) | Object::Struct(ustruct)) = &*info.ptr else {
+                continue;
+            };
+
+            let mut referenced = ustruct.parent.into_iter().collect::<Vec<_>>();
+            for field in ustruct.fields.iter() {
+                if let Field::Property { kind, .. } = field {
+                    kind.collect_fqns(&mut referenced);
+                }
+            }
+            for func in ustruct.functions.borrow().iter() {
+                for arg in func.args.iter() {
+                    arg.kind.collect_fqns(&mut referenced);
+                }
+            }
+
+            if let Some(fqn) = referenced.iter().find(|f| !sdk.owned.contains_key(f)) {
+                bail!("{} references unknown object {fqn}", ustruct.fqn);
+            }
+        }
+
+        Ok(sdk)
+    }
+
     pub fn lookup(&self, fqn: &Fqn) -> Option<&ObjectInfo> {
         self.owned.get(fqn)
     }
 
+    /// Looks up a function inside of the class or struct it belongs to.
+    pub fn lookup_function(&self, fqn: &Fqn) -> Option<Ref<'_, Function>> {
+        let parts = fqn.parts();
+        if parts.len() < 2 {
+            return None;
+        }
+
+        let owner = Fqn::from_parts(parts[1..].iter().copied());
+        let (Object::Class(owner) | Object::Struct(owner)) = &*self.lookup(&owner)?.ptr else {
+            return None;
+        };
+
+        Ref::filter_map(owner.functions.borrow(), |f| {
+            f.iter().find(|f| f.fqn == *fqn)
+        })
+        .ok()
+    }
+
     pub fn retrieve_key(&mut self, package_ident: &str) -> NodeIndex {
-        let merged_ident = State::get()
-            .options
-            .merge
-            .get(package_ident)
-            .map(|s| s.as_str())
-            .unwrap_or(package_ident);
-
-        if let Some(key) = self.indices.get(merged_ident) {
+        if let Some(key) = self.indices.get(package_ident) {
             *key
         } else {
-            let k: Rc<str> = merged_ident.into();
+            let k: Rc<str> = package_ident.into();
             let v = self.packages.add_node(Package::empty(&k));
             self.indices.insert(k, v);
             v
@@ -118,19 +287,22 @@ impl Sdk {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Enum {
+    #[serde(with = "serde_fqn")]
     pub fqn: Fqn,
     pub ident: String,
     pub layout: Layout,
     pub variants: Vec<(String, i64)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Struct {
+    #[serde(with = "serde_fqn")]
     pub fqn: Fqn,
     pub index: u32,
     pub is_uobject: bool,
+    #[serde(with = "serde_fqn::option")]
     pub parent: Option<Fqn>,
     pub ident: String,
     pub shrink: Cell<Option<usize>>,
@@ -139,23 +311,24 @@ pub struct Struct {
     pub functions: RefCell<Vec<Function>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Function {
     pub ident: String,
+    #[serde(with = "serde_fqn")]
     pub fqn: Fqn,
     pub index: u32,
     pub flags: FunctionFlags,
     pub args: Vec<FunctionArg>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionArg {
     pub name: String,
     pub kind: PropertyKind,
     pub flags: PropertyFlags,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Field {
     Property {
         name: String,
@@ -181,7 +354,7 @@ impl Field {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FieldOptions {
     pub offset: usize,
     pub elem_size: usize,
@@ -194,7 +367,7 @@ impl FieldOptions {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[rustfmt::skip]
 pub enum PropertyKind {
     Bool,
@@ -204,8 +377,17 @@ pub enum PropertyKind {
     Name,        // FName
     String,      // FString
     Text,        // FText
-    Ptr(Fqn),    // Pointer to an object
-    Inline(Fqn), // Inline enum or a struct
+    Ptr(#[serde(with = "serde_fqn")] Fqn),       // Pointer to an object
+    Inline(#[serde(with = "serde_fqn")] Fqn),    // Inline enum or a struct
+    Weak(#[serde(with = "serde_fqn")] Fqn),      // TWeakObjectPtr
+    Soft(#[serde(with = "serde_fqn")] Fqn),      // TSoftObjectPtr
+    SoftClass(#[serde(with = "serde_fqn")] Fqn), // TSoftClassPtr
+    Lazy(#[serde(with = "serde_fqn")] Fqn),      // TLazyObjectPtr
+    Interface(#[serde(with = "serde_fqn")] Fqn), // TScriptInterface
+    Delegate(#[serde(with = "serde_fqn")] Fqn),          // FScriptDelegate, signature function
+    MulticastDelegate(#[serde(with = "serde_fqn")] Fqn), // FMulticastScriptDelegate
+    SparseDelegate(#[serde(with = "serde_fqn")] Fqn),    // FSparseDelegate
+    FieldPath(String),                                  // TFieldPath, name of the field class
     Array {
         kind: Box<PropertyKind>,
         size: usize,
@@ -218,3 +400,63 @@ pub enum PropertyKind {
     }, // TMap,
     Unknown
 }
+
+impl PropertyKind {
+    /// Pushes every object this kind refers to.
+    pub fn collect_fqns(&self, out: &mut Vec<Fqn>) {
+        match self {
+            PropertyKind::Ptr(fqn)
+            | PropertyKind::Inline(fqn)
+            | PropertyKind::Weak(fqn)
+            | PropertyKind::Soft(fqn)
+            | PropertyKind::SoftClass(fqn)
+            | PropertyKind::Lazy(fqn)
+            | PropertyKind::Interface(fqn) => out.push(*fqn),
+            PropertyKind::Array { kind, .. }
+            | PropertyKind::Vec(kind)
+            | PropertyKind::Set(kind) => kind.collect_fqns(out),
+            PropertyKind::Map { key, value } => {
+                key.collect_fqns(out);
+                value.collect_fqns(out);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Display for PropertyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyKind::Bool => write!(f, "bool"),
+            PropertyKind::Int8 => write!(f, "i8"),
+            PropertyKind::Int16 => write!(f, "i16"),
+            PropertyKind::Int32 => write!(f, "i32"),
+            PropertyKind::Int64 => write!(f, "i64"),
+            PropertyKind::UInt8 => write!(f, "u8"),
+            PropertyKind::UInt16 => write!(f, "u16"),
+            PropertyKind::UInt32 => write!(f, "u32"),
+            PropertyKind::UInt64 => write!(f, "u64"),
+            PropertyKind::Float32 => write!(f, "f32"),
+            PropertyKind::Float64 => write!(f, "f64"),
+            PropertyKind::Name => write!(f, "FName"),
+            PropertyKind::String => write!(f, "FString"),
+            PropertyKind::Text => write!(f, "FText"),
+            PropertyKind::Ptr(fqn) => write!(f, "*{fqn}"),
+            PropertyKind::Inline(fqn) => write!(f, "{fqn}"),
+            PropertyKind::Weak(fqn) => write!(f, "TWeakObjectPtr<{fqn}>"),
+            PropertyKind::Soft(fqn) => write!(f, "TSoftObjectPtr<{fqn}>"),
+            PropertyKind::SoftClass(fqn) => write!(f, "TSoftClassPtr<{fqn}>"),
+            PropertyKind::Lazy(fqn) => write!(f, "TLazyObjectPtr<{fqn}>"),
+            PropertyKind::Interface(fqn) => write!(f, "TScriptInterface<{fqn}>"),
+            PropertyKind::Delegate(fqn) => write!(f, "TScriptDelegate<{fqn}>"),
+            PropertyKind::MulticastDelegate(fqn) => write!(f, "TMulticastScriptDelegate<{fqn}>"),
+            PropertyKind::SparseDelegate(fqn) => write!(f, "FSparseDelegate<{fqn}>"),
+            PropertyKind::FieldPath(class) => write!(f, "TFieldPath<F{class}>"),
+            PropertyKind::Array { kind, size } => write!(f, "[{kind}; {size}]"),
+            PropertyKind::Vec(kind) => write!(f, "TArray<{kind}>"),
+            PropertyKind::Set(kind) => write!(f, "TSet<{kind}>"),
+            PropertyKind::Map { key, value } => write!(f, "TMap<{key}, {value}>"),
+            PropertyKind::Unknown => write!(f, "?"),
+        }
+    }
+}
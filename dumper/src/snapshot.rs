@@ -0,0 +1,249 @@
+use crate::External;
+use anyhow::{ensure, Context, Result};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+const MAGIC: &[u8; 8] = b"UESNAP\0\0";
+const VERSION: u32 = 1;
+
+/// Set of captured memory regions that can be used in place of a live process.
+#[derive(Default)]
+pub struct Snapshot {
+    base: usize,
+    // Regions never overlap, but might be adjacent to each other.
+    regions: BTreeMap<usize, Box<[u8]>>,
+}
+
+impl Snapshot {
+    pub fn new(base: usize) -> Self {
+        Self {
+            base,
+            regions: BTreeMap::new(),
+        }
+    }
+
+    /// Base address of the main module at the moment of capture.
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Total amount of captured bytes.
+    pub fn size(&self) -> usize {
+        self.regions.values().map(|r| r.len()).sum()
+    }
+
+    pub fn insert(&mut self, address: usize, data: &[u8]) {
+        let (mut start, mut end) = (address, address + data.len());
+        if data.is_empty() {
+            return;
+        }
+
+        if let Some((from, region)) = self.regions.range(..=start).next_back() {
+            if end <= from + region.len() {
+                // Already captured.
+                return;
+            }
+        }
+
+        let overlapping = self
+            .regions
+            .range(..end)
+            .rev()
+            .take_while(|(from, region)| *from + region.len() > start)
+            .map(|(from, _)| *from)
+            .collect::<Vec<_>>();
+
+        if overlapping.is_empty() {
+            self.regions.insert(address, data.into());
+            return;
+        }
+
+        let olds = overlapping
+            .into_iter()
+            .map(|from| (from, self.regions.remove(&from).unwrap()))
+            .collect::<Vec<_>>();
+        for (from, region) in olds.iter() {
+            start = start.min(*from);
+            end = end.max(from + region.len());
+        }
+
+        let mut merged = vec![0; end - start];
+        for (from, region) in olds.iter() {
+            merged[from - start..from - start + region.len()].copy_from_slice(region);
+        }
+        merged[address - start..address - start + data.len()].copy_from_slice(data);
+
+        self.regions.insert(start, merged.into_boxed_slice());
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open snapshot {}", path.display()))?;
+
+        Self::read_from(BufReader::new(file))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    pub fn read_from(mut r: impl Read) -> Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        r.read_exact(&mut magic)?;
+        ensure!(&magic == MAGIC, "Not a snapshot file");
+
+        let version = read_u32(&mut r)?;
+        ensure!(
+            version == VERSION,
+            "Unsupported snapshot version {version}, expected {VERSION}"
+        );
+
+        let mut snapshot = Self::new(read_u64(&mut r)? as usize);
+        for _ in 0..read_u64(&mut r)? {
+            let address = read_u64(&mut r)? as usize;
+            let len = read_u64(&mut r)? as usize;
+
+            let mut data = vec![0; len];
+            r.read_exact(&mut data)?;
+            snapshot.insert(address, &data);
+        }
+
+        Ok(snapshot)
+    }
+
+    pub fn write_to(&self, mut w: impl Write) -> Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(self.base as u64).to_le_bytes())?;
+        w.write_all(&(self.regions.len() as u64).to_le_bytes())?;
+
+        for (address, data) in self.regions.iter() {
+            w.write_all(&(*address as u64).to_le_bytes())?;
+            w.write_all(&(data.len() as u64).to_le_bytes())?;
+            w.write_all(data)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl External for Snapshot {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> Result<()> {
+        let (mut cursor, mut buf) = (address, buf);
+
+        // Read might span across several adjacent regions.
+        while !buf.is_empty() {
+            let (from, region) = self
+                .regions
+                .range(..=cursor)
+                .next_back()
+                .filter(|(from, region)| cursor < *from + region.len())
+                .with_context(|| format!("Address {cursor:#X} is missing from the snapshot"))?;
+
+            let offset = cursor - from;
+            let len = buf.len().min(region.len() - offset);
+            buf[..len].copy_from_slice(&region[offset..offset + len]);
+
+            buf = &mut buf[len..];
+            cursor += len;
+        }
+
+        Ok(())
+    }
+}
+
+/// Forwards reads to the inner [`External`] and records every region that was read.
+pub struct Recorder {
+    inner: Box<dyn External>,
+    snapshot: Arc<Mutex<Snapshot>>,
+}
+
+impl Recorder {
+    pub fn new(inner: Box<dyn External>, base: usize) -> Self {
+        Self {
+            inner,
+            snapshot: Arc::new(Mutex::new(Snapshot::new(base))),
+        }
+    }
+
+    /// Buffer the regions are recorded into, it stays available after the recorder
+    /// was handed over to the dumper.
+    pub fn snapshot(&self) -> Arc<Mutex<Snapshot>> {
+        self.snapshot.clone()
+    }
+}
+
+impl External for Recorder {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_buf(address, buf)?;
+        self.snapshot.lock().unwrap().insert(address, buf);
+
+        Ok(())
+    }
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Recorder, Snapshot};
+    use crate::External;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut snapshot = Snapshot::new(0x1000);
+        snapshot.insert(0x1000, &[1, 2, 3, 4]);
+        snapshot.insert(0x1004, &[5, 6]);
+        snapshot.insert(0x1002, &[3, 4, 5, 6, 7]);
+        snapshot.insert(0x2000, &[8]);
+
+        let mut file = vec![];
+        snapshot.write_to(&mut file).unwrap();
+        let snapshot = Snapshot::read_from(&file[..]).unwrap();
+
+        assert_eq!(snapshot.base(), 0x1000);
+        assert_eq!(snapshot.size(), 8);
+
+        let mut buf = [0; 7];
+        snapshot.read_buf(0x1000, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6, 7]);
+        assert!(snapshot.read_buf(0x1ffe, &mut [0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut source = Snapshot::new(0x1000);
+        source.insert(0x1000, &[1, 2, 3, 4]);
+
+        let recorder = Recorder::new(Box::new(source), 0x1000);
+        let snapshot = recorder.snapshot();
+        let external: Box<dyn External> = Box::new(recorder);
+        assert_eq!(external.read::<u16>(0x1002).unwrap(), 0x0403);
+        drop(external);
+
+        let snapshot = snapshot.lock().unwrap();
+        assert_eq!(snapshot.size(), 2);
+        assert_eq!(snapshot.base(), 0x1000);
+    }
+}
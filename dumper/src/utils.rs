@@ -1,15 +1,19 @@
 use crate::{
     engine::{BoolVars, FBoolProperty, FPropertyPtr},
     sdk::PropertyKind,
+    Dumper,
 };
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashMap},
     fmt::{self, Debug, Display},
+    hash::Hasher,
     mem::take,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Layout {
     pub size: usize,
     pub align: usize,
@@ -46,7 +50,7 @@ fn test_align_layout() {
     assert_eq!(layout.get_aligned_size(), 16);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Bitfield {
     pub name: String,
     // Bit offset
@@ -56,7 +60,7 @@ pub struct Bitfield {
 }
 
 // Although groups might be bigger than 1 byte, I've never seen such a thing.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BitfieldGroup {
     pub offset: usize,
     pub items: Vec<Bitfield>,
@@ -71,6 +75,7 @@ pub struct BitfieldAccumulator {
 impl BitfieldAccumulator {
     pub fn accumulate(
         &mut self,
+        dumper: &Dumper,
         name: &str,
         fproperty: FPropertyPtr,
         kind: &PropertyKind,
@@ -81,7 +86,7 @@ impl BitfieldAccumulator {
                 byte_mask,
                 field_mask,
                 ..
-            } = fproperty.cast::<FBoolProperty>().vars()?;
+            } = fproperty.cast::<FBoolProperty>().vars(dumper)?;
 
             let make_field = || Bitfield {
                 len: (byte_mask >> byte_mask.trailing_zeros()).trailing_ones(),
@@ -160,3 +165,23 @@ pub fn sanitize_ident(ident: &str) -> Cow<str> {
         Cow::Borrowed(ident)
     }
 }
+
+/// Appends a counter to repeated names so generated members don't collide.
+#[derive(Default)]
+pub struct NameDedup(HashMap<u64, usize>);
+
+impl NameDedup {
+    pub fn entry<'n>(&mut self, name: &'n str) -> Cow<'n, str> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_usize(name.len());
+        hasher.write(name.as_bytes());
+
+        let i = self.0.entry(hasher.finish()).or_insert(0);
+        *i += 1;
+
+        match *i {
+            1 => Cow::Borrowed(name),
+            j => Cow::Owned(format!("{name}_{j}")),
+        }
+    }
+}
@@ -1,14 +1,90 @@
+use crate::{FMemory, GlobalContext};
+use memflex::assert_size;
+#[cfg(feature = "parking_lot")]
+use parking_lot::Mutex;
+#[cfg(feature = "spin")]
+use spin::Mutex;
 use std::{
-    alloc::{alloc, Layout},
+    alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout},
     char::decode_utf16,
+    collections::BTreeSet,
     fmt,
-    marker::PhantomData,
-    mem::forget,
+    mem::{size_of, ManuallyDrop},
     ops::{Deref, DerefMut},
-    ptr::NonNull,
+    ptr::{copy_nonoverlapping, drop_in_place, NonNull},
     slice::{from_raw_parts, from_raw_parts_mut},
 };
 
+// Addresses of live buffers owned by the Rust allocator. `TArray` must keep the layout of the engine,
+// so the ownership is tracked per buffer here. Any other buffer was allocated by the game.
+static RUST_BUFFERS: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+/// Number of live buffers owned by the Rust allocator,
+/// the engine allocator can't be enabled while there are any.
+pub(crate) fn rust_buffers() -> usize {
+    RUST_BUFFERS.lock().len()
+}
+
+// Allocator is fixed once the context is initialized, see `GlobalContext::init`.
+#[inline]
+fn allocator() -> Option<FMemory> {
+    GlobalContext::try_get().and_then(|c| c.allocator())
+}
+
+/// Reallocates a buffer of `TArray` with the engine allocator if there is one.
+unsafe fn realloc_array<T>(ptr: Option<NonNull<T>>, old: usize, new: usize) -> NonNull<T> {
+    assert!(size_of::<T>() != 0, "Zero sized elements are not supported");
+
+    let layout = Layout::array::<T>(new).expect("TArray is too large");
+    let data = match (allocator(), ptr) {
+        (Some(memory), Some(ptr)) => {
+            (memory.realloc)(ptr.as_ptr().cast(), layout.size(), layout.align() as u32)
+        }
+        (Some(memory), None) => (memory.malloc)(layout.size(), layout.align() as u32),
+        (None, ptr) => {
+            let data = match ptr {
+                Some(ptr) if RUST_BUFFERS.lock().remove(&(ptr.as_ptr() as usize)) => realloc(
+                    ptr.as_ptr().cast(),
+                    Layout::array::<T>(old).unwrap(),
+                    layout.size(),
+                ),
+                // Buffer of the game can't be grown without the engine allocator,
+                // elements are moved into a new one and the old one is leaked.
+                Some(ptr) => {
+                    let data = alloc(layout);
+                    if !data.is_null() {
+                        copy_nonoverlapping(ptr.as_ptr(), data.cast(), old);
+                    }
+                    data
+                }
+                None => alloc(layout),
+            };
+            if !data.is_null() {
+                RUST_BUFFERS.lock().insert(data as usize);
+            }
+            data
+        }
+    };
+
+    match NonNull::new(data.cast()) {
+        Some(data) => data,
+        None => handle_alloc_error(layout),
+    }
+}
+
+unsafe fn free_array<T>(ptr: NonNull<T>, capacity: usize) {
+    match allocator() {
+        Some(memory) => (memory.free)(ptr.as_ptr().cast()),
+        None if RUST_BUFFERS.lock().remove(&(ptr.as_ptr() as usize)) => {
+            dealloc(ptr.as_ptr().cast(), Layout::array::<T>(capacity).unwrap())
+        }
+        // Buffers of the game can only be freed by the engine allocator.
+        None => {}
+    }
+}
+
+/// Owned array, the buffer is freed on drop. Buffers allocated by the game are leaked instead
+/// unless [`GlobalContext::with_allocator`] is used, see [`TArrayView`] for arrays owned by the game.
 #[repr(C)]
 pub struct TArray<T> {
     ptr: Option<NonNull<T>>,
@@ -17,6 +93,49 @@ pub struct TArray<T> {
 }
 
 impl<T> TArray<T> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            ptr: None,
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut this = Self::new();
+        this.reserve(capacity);
+        this
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len as usize + additional;
+        if required <= self.capacity() {
+            return;
+        }
+
+        let capacity = required.max(self.capacity() * 2).max(4);
+        self.ptr = Some(unsafe { realloc_array(self.ptr, self.capacity(), capacity) });
+        self.capacity = capacity.try_into().expect("TArray is too large");
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        unsafe {
+            self.ptr
+                .unwrap()
+                .as_ptr()
+                .add(self.len as usize)
+                .write(value)
+        };
+        self.len += 1;
+    }
+
     #[inline]
     pub fn as_slice(&self) -> &[T] {
         match self.ptr {
@@ -32,6 +151,24 @@ impl<T> TArray<T> {
             None => &mut [],
         }
     }
+
+    /// Gives up the ownership of the buffer, i.e. after passing the array to the game.
+    #[inline]
+    pub fn into_view(self) -> TArrayView<T> {
+        let this = ManuallyDrop::new(self);
+        TArrayView {
+            ptr: this.ptr,
+            len: this.len,
+            capacity: this.capacity,
+        }
+    }
+}
+
+impl<T> Default for TArray<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for TArray<T> {
@@ -41,52 +178,45 @@ impl<T: fmt::Debug> fmt::Debug for TArray<T> {
 }
 
 impl<T> From<Vec<T>> for TArray<T> {
-    fn from(mut vec: Vec<T>) -> Self {
-        let (ptr, len, capacity) = (vec.as_mut_ptr(), vec.len() as u32, vec.capacity() as u32);
-        forget(vec);
+    fn from(vec: Vec<T>) -> Self {
+        // Buffer of the vector can be adopted unless it has to be freed by the engine.
+        if allocator().is_some() || vec.capacity() == 0 || size_of::<T>() == 0 {
+            let mut this = Self::with_capacity(vec.len());
+            for value in vec {
+                this.push(value);
+            }
 
+            return this;
+        }
+
+        let mut vec = ManuallyDrop::new(vec);
+        let ptr = NonNull::new(vec.as_mut_ptr());
+        RUST_BUFFERS.lock().insert(vec.as_mut_ptr() as usize);
         Self {
-            ptr: NonNull::new(ptr),
-            capacity,
-            len,
+            ptr,
+            len: vec.len().try_into().expect("TArray is too large"),
+            capacity: vec.capacity().try_into().expect("TArray is too large"),
         }
     }
 }
 
 impl<T: Clone> Clone for TArray<T> {
     fn clone(&self) -> Self {
-        if let Some(ptr) = self.ptr {
-            unsafe {
-                let data = alloc(Layout::array::<T>(self.len as usize).unwrap()).cast::<T>();
-                if !data.is_null() {
-                    data.copy_from_nonoverlapping(ptr.as_ptr(), self.len as usize);
-                }
-
-                Self {
-                    ptr: NonNull::new(data),
-                    len: self.len,
-                    capacity: self.capacity,
-                }
-            }
-        } else {
-            Self {
-                ptr: None,
-                len: 0,
-                capacity: 0,
-            }
+        let mut this = Self::with_capacity(self.len());
+        for value in self.iter() {
+            this.push(value.clone());
         }
+
+        this
     }
 }
 
 impl<T> Drop for TArray<T> {
     fn drop(&mut self) {
         unsafe {
+            drop_in_place(self.as_mut_slice());
             if let Some(ptr) = self.ptr {
-                drop(Vec::from_raw_parts(
-                    ptr.as_ptr(),
-                    self.len as usize,
-                    self.capacity as usize,
-                ));
+                free_array(ptr, self.capacity());
             }
         }
     }
@@ -108,6 +238,65 @@ impl<T> DerefMut for TArray<T> {
     }
 }
 
+/// Array owned by the game, same layout as [`TArray`] but never frees the buffer.
+#[repr(C)]
+pub struct TArrayView<T> {
+    ptr: Option<NonNull<T>>,
+    len: u32,
+    capacity: u32,
+}
+
+impl<T> TArrayView<T> {
+    /// # Safety
+    /// * `ptr` must be valid for `len` reads and writes while the view is used.
+    #[inline]
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: u32, capacity: u32) -> Self {
+        Self {
+            ptr: NonNull::new(ptr),
+            len,
+            capacity,
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        match self.ptr {
+            Some(ptr) => unsafe { from_raw_parts(ptr.as_ptr(), self.len as usize) },
+            None => &[],
+        }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match self.ptr {
+            Some(ptr) => unsafe { from_raw_parts_mut(ptr.as_ptr(), self.len as usize) },
+            None => &mut [],
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TArrayView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+impl<T> Deref for TArrayView<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for TArrayView<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
 #[derive(Clone)]
 #[repr(transparent)]
 pub struct FString {
@@ -173,17 +362,64 @@ impl<T: AsRef<str>> PartialEq<T> for FString {
     }
 }
 
+/// Localized text, owned by the game. Only the display string can be read.
 #[repr(C)]
-pub struct TSet<T>([u8; 0x50], PhantomData<T>);
+pub struct FText {
+    // TSharedRef<ITextData, ESPMode::ThreadSafe>
+    data: Option<NonNull<FTextData>>,
+    controller: *const (),
+    flags: u32,
+}
+assert_size!(FText, 0x18);
 
+// TTextData<FTextHistory_Base>
 #[repr(C)]
-pub struct TMap<K, V> {
-    data: TSet<(K, V)>,
+struct FTextData {
+    vtable: *const (),
+    // TSharedPtr<FString, ESPMode::ThreadSafe>
+    localized: Option<NonNull<FString>>,
+    localized_controller: *const (),
+    history_vtable: *const (),
+    revision: i32,
+    source: FString,
+}
+
+impl FText {
+    /// Returns localized string if there is one, otherwise the source string.
+    pub fn display_string(&self) -> Option<&FString> {
+        let data = unsafe { self.data?.as_ref() };
+        match data.localized {
+            Some(localized) => Some(unsafe { localized.as_ref() }),
+            None => Some(&data.source),
+        }
+    }
+
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+impl fmt::Display for FText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.display_string() {
+            Some(string) => write!(f, "{string}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for FText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::FString;
+    use super::FTextData;
+    use crate::{FString, FText, TArray};
+    use std::{mem::ManuallyDrop, ptr::NonNull, rc::Rc};
 
     #[test]
     fn test_string() {
@@ -194,4 +430,61 @@ mod tests {
         drop(second);
         assert_eq!(first, "Foo");
     }
+
+    #[test]
+    fn test_array() {
+        let value = Rc::new(0);
+        let mut array = TArray::new();
+        for _ in 0..5 {
+            array.push(value.clone());
+        }
+        assert_eq!(array.len(), 5);
+        assert!(array.capacity() >= 5);
+
+        let copy = array.clone();
+        assert_eq!(Rc::strong_count(&value), 11);
+        drop(array);
+        drop(copy);
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        let vec = vec![1, 2, 3];
+        let ptr = vec.as_ptr();
+        let array = TArray::from(vec);
+        assert_eq!((array.as_ptr(), array.as_slice()), (ptr, &[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_text() {
+        let empty = FText {
+            data: None,
+            controller: std::ptr::null(),
+            flags: 0,
+        };
+        assert!(empty.display_string().is_none());
+        assert_eq!(std::mem::offset_of!(FTextData, source), 0x28);
+
+        let mut localized = ManuallyDrop::new(FString::from("Localized"));
+        let mut data = ManuallyDrop::new(FTextData {
+            vtable: std::ptr::null(),
+            localized: None,
+            localized_controller: std::ptr::null(),
+            history_vtable: std::ptr::null(),
+            revision: 0,
+            source: "Source".into(),
+        });
+        let text = FText {
+            data: NonNull::new(&mut *data),
+            controller: std::ptr::null(),
+            flags: 0,
+        };
+        assert_eq!(text.to_string(), "Source");
+
+        data.localized = NonNull::new(&mut *localized);
+        assert_eq!(text.to_string(), "Localized");
+
+        unsafe {
+            ManuallyDrop::drop(&mut localized);
+            ManuallyDrop::drop(&mut data);
+        }
+    }
 }
@@ -6,7 +6,7 @@ use std::collections::HashMap;
 pub trait Cache<Key> {
     type Data;
 
-    fn lookup(&self, key: &Key) -> Self::Data;
+    fn lookup(&self, key: &Key) -> Option<Self::Data>;
     fn insert(&self, key: Key, value: Self::Data);
 
     fn flush(&self);
@@ -31,12 +31,15 @@ impl<K, V> Default for AnyCache<K, V> {
 impl Cache<HashedFqn> for UObjectCache {
     type Data = Ptr<UObject>;
 
-    fn lookup(&self, hfqn: &HashedFqn) -> Ptr<UObject> {
-        let mut lock = self.0.lock();
-        let object = lock
-            .entry(*hfqn)
-            .or_insert_with(|| UObject::get_by_fqn(*hfqn).expect("Failed to find UObject"));
-        *object
+    /// Misses aren't cached, the object can still be loaded later.
+    fn lookup(&self, hfqn: &HashedFqn) -> Option<Ptr<UObject>> {
+        if let Some(object) = self.0.lock().get(hfqn) {
+            return Some(*object);
+        }
+
+        let object = UObject::get_by_fqn(*hfqn)?;
+        self.0.lock().insert(*hfqn, object);
+        Some(object)
     }
 
     fn flush(&self) {
@@ -48,4 +51,5 @@ impl Cache<HashedFqn> for UObjectCache {
     }
 }
 
-pub(crate) static DEFAULT_CACHE: Lazy<UObjectCache> = Lazy::new(UObjectCache::default);
+#[doc(hidden)]
+pub static DEFAULT_CACHE: Lazy<UObjectCache> = Lazy::new(UObjectCache::default);
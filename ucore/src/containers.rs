@@ -0,0 +1,427 @@
+use crate::{FName, Ptr, TArray};
+use std::{fmt, mem::ManuallyDrop, ptr::NonNull, slice::from_raw_parts};
+
+const INDEX_NONE: i32 = -1;
+
+/// `GetTypeHash` of the engine, used by [`TSet`] and [`TMap`] to find the hash bucket of a key.
+pub trait TypeHash {
+    fn type_hash(&self) -> u32;
+}
+
+macro_rules! impl_int_type_hash {
+    ($($ty:ty),*) => {
+        $(
+            impl TypeHash for $ty {
+                #[inline]
+                fn type_hash(&self) -> u32 {
+                    *self as u32
+                }
+            }
+        )*
+    };
+}
+impl_int_type_hash!(i8, u8, i16, u16, i32, u32);
+
+impl TypeHash for u64 {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        (*self as u32).wrapping_add(((*self >> 32) as u32).wrapping_mul(23))
+    }
+}
+
+impl TypeHash for i64 {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        (*self as u64).type_hash()
+    }
+}
+
+impl TypeHash for f32 {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        self.to_bits()
+    }
+}
+
+impl TypeHash for f64 {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        self.to_bits().type_hash()
+    }
+}
+
+impl TypeHash for FName {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        #[cfg(not(feature = "outline_name_number"))]
+        return self.index().wrapping_add(self.number());
+        #[cfg(feature = "outline_name_number")]
+        return self.index();
+    }
+}
+
+/// Same as `HashCombine` of the engine.
+fn hash_combine(mut a: u32, mut c: u32) -> u32 {
+    let mut b = 0x9e3779b9u32;
+    a = a.wrapping_add(b);
+
+    a = a.wrapping_sub(b).wrapping_sub(c) ^ (c >> 13);
+    b = b.wrapping_sub(c).wrapping_sub(a) ^ (a << 8);
+    c = c.wrapping_sub(a).wrapping_sub(b) ^ (b >> 13);
+    a = a.wrapping_sub(b).wrapping_sub(c) ^ (c >> 12);
+    b = b.wrapping_sub(c).wrapping_sub(a) ^ (a << 16);
+    c = c.wrapping_sub(a).wrapping_sub(b) ^ (b >> 5);
+    a = a.wrapping_sub(b).wrapping_sub(c) ^ (c >> 3);
+    b = b.wrapping_sub(c).wrapping_sub(a) ^ (a << 10);
+    c.wrapping_sub(a).wrapping_sub(b) ^ (b >> 15)
+}
+
+/// Same as `PointerHash` of the engine, lower 4 bits are ignored.
+fn pointer_hash<T: ?Sized>(ptr: *const T) -> u32 {
+    hash_combine((ptr.cast::<()>() as usize >> 4) as u32, 0)
+}
+
+impl<T: ?Sized> TypeHash for *const T {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        pointer_hash(*self)
+    }
+}
+
+impl<T: ?Sized> TypeHash for *mut T {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        pointer_hash(*self)
+    }
+}
+
+impl<T: ?Sized> TypeHash for Ptr<T> {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        pointer_hash(self.as_ptr())
+    }
+}
+
+impl<T> TypeHash for Option<Ptr<T>> {
+    #[inline]
+    fn type_hash(&self) -> u32 {
+        pointer_hash(self.map_or(std::ptr::null_mut(), |p| p.as_ptr()))
+    }
+}
+
+/// Bit array with 4 inline words.
+#[repr(C)]
+pub struct TBitArray {
+    inline_data: [u32; 4],
+    secondary_data: Option<NonNull<u32>>,
+    num_bits: i32,
+    max_bits: i32,
+}
+
+impl TBitArray {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.num_bits.max(0) as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn words(&self) -> &[u32] {
+        let len = self.len().div_ceil(32);
+        match self.secondary_data {
+            Some(data) => unsafe { from_raw_parts(data.as_ptr(), len) },
+            None => &self.inline_data[..len.min(4)],
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        if idx >= self.len() {
+            return None;
+        }
+
+        Some(self.words()[idx / 32] & (1 << (idx % 32)) != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len()).map(|i| self.get(i).unwrap())
+    }
+}
+
+impl fmt::Debug for TBitArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.iter() {
+            write!(f, "{}", bit as u8)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Free slots of a sparse array hold the free list links instead of the element.
+#[repr(C)]
+union TSparseArrayElement<T> {
+    element: ManuallyDrop<T>,
+    free_list_link: [i32; 2],
+}
+
+/// Array with holes, allocated slots are marked in `allocation_flags`.
+#[repr(C)]
+pub struct TSparseArray<T> {
+    data: TArray<TSparseArrayElement<T>>,
+    allocation_flags: TBitArray,
+    first_free_index: i32,
+    num_free_indices: i32,
+}
+
+impl<T> TSparseArray<T> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len() - self.num_free_indices.max(0) as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size of the array including free slots.
+    #[inline]
+    pub fn max_index(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        if !self.allocation_flags.get(idx)? {
+            return None;
+        }
+
+        self.data.get(idx).map(|e| unsafe { &*e.element })
+    }
+
+    /// Allocated elements along with their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        (0..self.max_index()).filter_map(|i| self.get(i).map(|e| (i, e)))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TSparseArray<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[repr(C)]
+struct TSetElement<T> {
+    value: T,
+    hash_next_id: i32,
+    hash_index: i32,
+}
+
+/// Hash buckets with a single inline bucket.
+#[repr(C)]
+struct TSetHash {
+    inline_data: i32,
+    secondary_data: Option<NonNull<i32>>,
+}
+
+#[repr(C)]
+pub struct TSet<T> {
+    elements: TSparseArray<TSetElement<T>>,
+    hash: TSetHash,
+    hash_size: i32,
+}
+
+impl<T> TSet<T> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.elements.iter().map(|(_, e)| &e.value)
+    }
+
+    fn buckets(&self) -> &[i32] {
+        let len = self.hash_size.max(0) as usize;
+        match self.hash.secondary_data {
+            Some(data) => unsafe { from_raw_parts(data.as_ptr(), len) },
+            None => std::slice::from_ref(&self.hash.inline_data)
+                .get(..len)
+                .unwrap_or(&[]),
+        }
+    }
+
+    /// Walks the bucket of `hash` until `eq` matches an element.
+    pub fn find_by_hash(&self, hash: u32, mut eq: impl FnMut(&T) -> bool) -> Option<&T> {
+        let buckets = self.buckets();
+        if buckets.is_empty() {
+            return None;
+        }
+
+        let mut id = buckets[hash as usize & (buckets.len() - 1)];
+        while id != INDEX_NONE {
+            let element = self.elements.get(id as usize)?;
+            if eq(&element.value) {
+                return Some(&element.value);
+            }
+
+            id = element.hash_next_id;
+        }
+
+        None
+    }
+}
+
+impl<T: TypeHash + PartialEq> TSet<T> {
+    #[inline]
+    pub fn get(&self, value: &T) -> Option<&T> {
+        self.find_by_hash(value.type_hash(), |v| v == value)
+    }
+
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        self.get(value).is_some()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Element of [`TMap`], same layout as `TTuple<K, V>`.
+#[repr(C)]
+pub struct TPair<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+#[repr(C)]
+pub struct TMap<K, V> {
+    pairs: TSet<TPair<K, V>>,
+}
+
+impl<K, V> TMap<K, V> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.pairs.iter().map(|p| (&p.key, &p.value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.pairs.iter().map(|p| &p.key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.pairs.iter().map(|p| &p.value)
+    }
+}
+
+impl<K: TypeHash + PartialEq, V> TMap<K, V> {
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.pairs
+            .find_by_hash(key.type_hash(), |p| p.key == *key)
+            .map(|p| &p.value)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for TMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        TBitArray, TMap, TPair, TSet, TSetElement, TSetHash, TSparseArray, TSparseArrayElement,
+        TypeHash,
+    };
+    use std::{mem::size_of, mem::ManuallyDrop, ptr::NonNull};
+
+    fn element(
+        key: i32,
+        value: u64,
+        hash_next_id: i32,
+    ) -> TSparseArrayElement<TSetElement<TPair<i32, u64>>> {
+        TSparseArrayElement {
+            element: ManuallyDrop::new(TSetElement {
+                value: TPair { key, value },
+                hash_next_id,
+                hash_index: key & 3,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        assert_eq!(size_of::<TBitArray>(), 0x20);
+        assert_eq!(size_of::<TSparseArray<u32>>(), 0x38);
+        assert_eq!(size_of::<TSet<u32>>(), 0x50);
+        assert_eq!(size_of::<TMap<u32, u64>>(), 0x50);
+
+        // Keys 1 and 5 share the second bucket, slot 1 is free.
+        let mut buckets = [-1, 3, 2, -1];
+        let elements = vec![
+            element(1, 10, -1),
+            TSparseArrayElement {
+                free_list_link: [-1, -1],
+            },
+            element(2, 20, -1),
+            element(5, 50, 0),
+        ];
+        let map = TMap {
+            pairs: TSet {
+                elements: TSparseArray {
+                    data: elements.into(),
+                    allocation_flags: TBitArray {
+                        inline_data: [0b1101, 0, 0, 0],
+                        secondary_data: None,
+                        num_bits: 4,
+                        max_bits: 128,
+                    },
+                    first_free_index: 1,
+                    num_free_indices: 1,
+                },
+                hash: TSetHash {
+                    inline_data: 0,
+                    secondary_data: NonNull::new(buckets.as_mut_ptr()),
+                },
+                hash_size: 4,
+            },
+        };
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 5]);
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.get(&5), Some(&50));
+        assert_eq!(map.get(&2), Some(&20));
+        assert_eq!(map.get(&3), None);
+        assert_eq!(
+            (-1i64).type_hash(),
+            u32::MAX.wrapping_add(u32::MAX.wrapping_mul(23))
+        );
+    }
+}
@@ -1,12 +1,46 @@
-use crate::{FChunkedFixedUObjectArray, FNamePool, Ptr};
+use crate::{
+    api::rust_buffers, FChunkedFixedUObjectArray, FName, FNameLayout, FNamePool,
+    FUObjectItemLayout, Ptr, UStructLayout,
+};
 use once_cell::sync::OnceCell;
 use std::ptr::{null_mut, NonNull};
 
+/// Engine allocator, i.e. `FMemory::Malloc`, `FMemory::Realloc` and `FMemory::Free`.
+#[derive(Debug, Clone, Copy)]
+pub struct FMemory {
+    pub malloc: unsafe extern "C" fn(count: usize, alignment: u32) -> *mut u8,
+    pub realloc: unsafe extern "C" fn(original: *mut u8, count: usize, alignment: u32) -> *mut u8,
+    pub free: unsafe extern "C" fn(original: *mut u8),
+}
+
+/// `FName::FName(const TCHAR* Name, EFindName FindType)`.
+pub type FNameConstructor =
+    unsafe extern "C" fn(this: *mut FName, name: *const u16, find_type: u32) -> *mut FName;
+
+/// Layout of the game the SDK was dumped from, generated SDKs provide it as `usdk::layout()`.
+#[derive(Debug, Clone, Copy)]
+pub struct EngineLayout {
+    /// Offsets of `FNamePool`, `FChunkedFixedUObjectArray`, `GWorld` and `GEngine`
+    /// relative to the module base.
+    pub names: Option<usize>,
+    pub objects: Option<usize>,
+    pub world: Option<usize>,
+    pub engine: Option<usize>,
+    pub name: FNameLayout,
+    pub object_item: FUObjectItemLayout,
+    pub ustruct: UStructLayout,
+}
+
 pub struct GlobalContext {
     names: *mut FNamePool,
     objects: *mut FChunkedFixedUObjectArray,
     engine: *mut *mut (),
     world: *mut *mut (),
+    allocator: Option<FMemory>,
+    name_layout: FNameLayout,
+    object_item_layout: FUObjectItemLayout,
+    ustruct_layout: UStructLayout,
+    fname_constructor: Option<FNameConstructor>,
 }
 
 unsafe impl Sync for GlobalContext {}
@@ -21,9 +55,31 @@ impl GlobalContext {
             objects,
             engine: null_mut(),
             world: null_mut(),
+            allocator: None,
+            name_layout: FNameLayout::default(),
+            object_item_layout: FUObjectItemLayout::default(),
+            ustruct_layout: UStructLayout::default(),
+            fname_constructor: None,
         }
     }
 
+    /// Context of the game module loaded at `base`, `None` if names or objects offsets are missing.
+    pub fn from_layout(base: *mut u8, layout: &EngineLayout) -> Option<Self> {
+        let global = |offset: usize| base.wrapping_add(offset);
+        let mut this = Self::new(global(layout.names?).cast(), global(layout.objects?).cast())
+            .with_name_layout(layout.name)
+            .with_object_item_layout(layout.object_item)
+            .with_ustruct_layout(layout.ustruct);
+        if let Some(engine) = layout.engine {
+            this = this.with_engine(global(engine).cast());
+        }
+        if let Some(world) = layout.world {
+            this = this.with_world(global(world).cast());
+        }
+
+        Some(this)
+    }
+
     pub fn with_engine(mut self, engine: *mut *mut ()) -> Self {
         self.engine = engine;
         self
@@ -34,7 +90,42 @@ impl GlobalContext {
         self
     }
 
+    /// Overrides the default layout of `FNamePool`, needed by games with modified name entries.
+    pub fn with_name_layout(mut self, layout: FNameLayout) -> Self {
+        self.name_layout = layout;
+        self
+    }
+
+    /// Overrides the default layout of `FUObjectItem`, i.e. for games with 0x20 byte items.
+    pub fn with_object_item_layout(mut self, layout: FUObjectItemLayout) -> Self {
+        self.object_item_layout = layout;
+        self
+    }
+
+    /// Overrides the default offsets of `UStruct::SuperStruct` and `UStruct::ChildProperties`.
+    pub fn with_ustruct_layout(mut self, layout: UStructLayout) -> Self {
+        self.ustruct_layout = layout;
+        self
+    }
+
+    /// Enables [`FName::find_or_add`].
+    pub fn with_fname_constructor(mut self, constructor: FNameConstructor) -> Self {
+        self.fname_constructor = Some(constructor);
+        self
+    }
+
+    /// Makes `TArray` and `FString` use the engine allocator, so they can be freed by the game.
+    /// [`GlobalContext::init`] panics if arrays allocated by the Rust allocator are still alive.
+    pub fn with_allocator(mut self, allocator: FMemory) -> Self {
+        self.allocator = Some(allocator);
+        self
+    }
+
     pub fn init(self) -> &'static Self {
+        assert!(
+            self.allocator.is_none() || rust_buffers() == 0,
+            "Arrays allocated by the Rust allocator can't be freed by the engine allocator"
+        );
         let result = CONTEXT.set(self.into());
         assert!(result.is_ok(), "GlobalContext was already initialized");
 
@@ -48,6 +139,36 @@ impl GlobalContext {
             .expect("GlobalContext has not yet been initialized")
     }
 
+    #[inline]
+    pub fn try_get() -> Option<&'static Self> {
+        CONTEXT.get().map(|c| &**c)
+    }
+
+    #[inline]
+    pub fn name_layout(&self) -> FNameLayout {
+        self.name_layout
+    }
+
+    #[inline]
+    pub fn object_item_layout(&self) -> FUObjectItemLayout {
+        self.object_item_layout
+    }
+
+    #[inline]
+    pub fn ustruct_layout(&self) -> UStructLayout {
+        self.ustruct_layout
+    }
+
+    #[inline]
+    pub fn fname_constructor(&self) -> Option<FNameConstructor> {
+        self.fname_constructor
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> Option<FMemory> {
+        self.allocator
+    }
+
     pub fn name_pool(&self) -> &'static FNamePool {
         unsafe { self.names.as_ref().unwrap() }
     }
@@ -0,0 +1,170 @@
+use crate::{FName, FWeakObjectPtr, Ptr, TArray, UObject};
+use std::{marker::PhantomData, ops::Deref};
+
+/// Implemented by the generated parameter structs of delegate signatures.
+/// # Safety
+/// * Layout of the struct must match the parameters of the signature `UFunction`.
+pub unsafe trait DelegateSignature {
+    const PROCESS_EVENT_INDEX: usize;
+}
+
+/// Single function bound to an object.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FScriptDelegate {
+    object: FWeakObjectPtr,
+    function_name: FName,
+}
+
+impl FScriptDelegate {
+    #[inline]
+    pub fn object(&self) -> Option<Ptr<UObject>> {
+        self.object.get()
+    }
+
+    #[inline]
+    pub fn function_name(&self) -> FName {
+        self.function_name
+    }
+
+    #[inline]
+    pub fn is_bound(&self) -> bool {
+        self.object().is_some()
+    }
+
+    /// Bound `UFunction`, looked up by name in the class of the bound object.
+    pub fn function(&self) -> Option<Ptr<UObject>> {
+        self.object()?.find_function(self.function_name)
+    }
+
+    /// Calls the bound function, returns `false` if the delegate is not bound.
+    /// # Safety
+    /// * See [`UObject::process_event`], `Args` must match the signature of the delegate.
+    pub unsafe fn process_delegate<Args>(&self, index: usize, args: *mut Args) -> bool {
+        let Some(mut object) = self.object() else {
+            return false;
+        };
+        let Some(function) = object.find_function(self.function_name) else {
+            return false;
+        };
+
+        object.process_event(index, function, args);
+        true
+    }
+}
+
+/// List of delegates that are invoked together.
+#[repr(C)]
+pub struct FMulticastScriptDelegate {
+    invocation_list: TArray<FScriptDelegate>,
+}
+
+impl FMulticastScriptDelegate {
+    #[inline]
+    pub fn invocation_list(&self) -> &[FScriptDelegate] {
+        self.invocation_list.as_slice()
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.invocation_list().iter().any(FScriptDelegate::is_bound)
+    }
+
+    /// Calls every bound function, returns the amount of functions called.
+    /// # Safety
+    /// * See [`FScriptDelegate::process_delegate`].
+    pub unsafe fn process_multicast_delegate<Args>(&self, index: usize, args: *mut Args) -> usize {
+        self.invocation_list()
+            .iter()
+            .filter(|delegate| delegate.process_delegate(index, args))
+            .count()
+    }
+}
+
+/// Multicast delegate that stores its invocation list outside of the object.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FSparseDelegate {
+    is_bound: bool,
+}
+
+impl FSparseDelegate {
+    #[inline]
+    pub fn is_bound(&self) -> bool {
+        self.is_bound
+    }
+}
+
+#[repr(transparent)]
+pub struct TScriptDelegate<S> {
+    delegate: FScriptDelegate,
+    _marker: PhantomData<S>,
+}
+
+impl<S: DelegateSignature> TScriptDelegate<S> {
+    #[inline]
+    pub fn execute(&self, params: &mut S) -> bool {
+        unsafe {
+            self.delegate
+                .process_delegate(S::PROCESS_EVENT_INDEX, params)
+        }
+    }
+}
+
+impl<S> Deref for TScriptDelegate<S> {
+    type Target = FScriptDelegate;
+
+    fn deref(&self) -> &Self::Target {
+        &self.delegate
+    }
+}
+
+#[repr(transparent)]
+pub struct TMulticastScriptDelegate<S> {
+    delegate: FMulticastScriptDelegate,
+    _marker: PhantomData<S>,
+}
+
+impl<S: DelegateSignature> TMulticastScriptDelegate<S> {
+    #[inline]
+    pub fn broadcast(&self, params: &mut S) -> usize {
+        unsafe {
+            self.delegate
+                .process_multicast_delegate(S::PROCESS_EVENT_INDEX, params)
+        }
+    }
+}
+
+impl<S> Deref for TMulticastScriptDelegate<S> {
+    type Target = FMulticastScriptDelegate;
+
+    fn deref(&self) -> &Self::Target {
+        &self.delegate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DelegateSignature, FMulticastScriptDelegate, TMulticastScriptDelegate};
+    use crate::TArray;
+    use std::{marker::PhantomData, mem::size_of};
+
+    struct Signature;
+
+    unsafe impl DelegateSignature for Signature {
+        const PROCESS_EVENT_INDEX: usize = 0x44;
+    }
+
+    #[test]
+    fn test_unbound_delegates() {
+        assert_eq!(size_of::<FMulticastScriptDelegate>(), 0x10);
+
+        let delegate = TMulticastScriptDelegate::<Signature> {
+            delegate: FMulticastScriptDelegate {
+                invocation_list: TArray::from(vec![]),
+            },
+            _marker: PhantomData,
+        };
+        assert!(!delegate.is_bound());
+        assert_eq!(delegate.broadcast(&mut Signature), 0);
+    }
+}
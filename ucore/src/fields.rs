@@ -0,0 +1,182 @@
+use crate::{FName, GlobalContext, HashedFqn, Ptr, TArray, TWeakObjectPtr, UClass, UObject};
+use std::{iter::successors, marker::PhantomData, ops::Deref, ptr::NonNull};
+
+#[repr(C)]
+pub struct FFieldClass {
+    name: FName,
+}
+
+impl FFieldClass {
+    #[inline]
+    pub fn name(&self) -> FName {
+        self.name
+    }
+}
+
+/// Owner of a field, either an `UObject` or another `FField`.
+#[repr(C)]
+struct FFieldVariant {
+    container: *mut (),
+    is_uobject: bool,
+}
+
+#[repr(C)]
+pub struct FField {
+    vmt: *const (),
+    class: Ptr<FFieldClass>,
+    owner: FFieldVariant,
+    next: Option<Ptr<FField>>,
+    name: FName,
+    flags: u32,
+}
+
+impl FField {
+    #[inline]
+    pub fn class(&self) -> Ptr<FFieldClass> {
+        self.class
+    }
+
+    #[inline]
+    pub fn name(&self) -> FName {
+        self.name
+    }
+
+    #[inline]
+    pub fn next(&self) -> Option<Ptr<FField>> {
+        self.next
+    }
+
+    pub fn owner_object(&self) -> Option<Ptr<UObject>> {
+        self.owner
+            .is_uobject
+            .then(|| NonNull::new(self.owner.container.cast()).map(Ptr))
+            .flatten()
+    }
+
+    pub fn owner_field(&self) -> Option<Ptr<FField>> {
+        (!self.owner.is_uobject)
+            .then(|| NonNull::new(self.owner.container.cast()).map(Ptr))
+            .flatten()
+    }
+}
+
+#[repr(C)]
+pub struct FProperty {
+    field: FField,
+    array_dim: i32,
+    element_size: i32,
+    flags: u64,
+    rep_index: u16,
+    replication_condition: u8,
+    offset: i32,
+}
+
+impl FProperty {
+    #[inline]
+    pub fn array_dim(&self) -> i32 {
+        self.array_dim
+    }
+
+    #[inline]
+    pub fn element_size(&self) -> i32 {
+        self.element_size
+    }
+
+    #[inline]
+    pub fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    #[inline]
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+}
+
+impl Deref for FProperty {
+    type Target = FField;
+
+    fn deref(&self) -> &Self::Target {
+        &self.field
+    }
+}
+
+#[repr(C)]
+pub struct FFieldPath {
+    resolved_field: Option<Ptr<FField>>,
+    resolved_owner: TWeakObjectPtr<UClass>,
+    /// From the innermost field up to the package.
+    path: TArray<FName>,
+}
+
+impl FFieldPath {
+    /// Uses the cached field while its owner is alive, otherwise resolves the path.
+    pub fn get(&self) -> Option<Ptr<FField>> {
+        match (self.resolved_field, self.resolved_owner.get()) {
+            (Some(field), Some(_)) => Some(field),
+            _ => self.resolve(),
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &[FName] {
+        self.path.as_slice()
+    }
+
+    /// Only fields that are declared directly in a struct can be resolved.
+    fn resolve(&self) -> Option<Ptr<FField>> {
+        let (name, owner_path) = self.path().split_first()?;
+        let context = GlobalContext::get();
+        let fqn = HashedFqn::from_names(context.name_pool(), owner_path.iter().copied())?;
+        let owner = context
+            .chunked_fixed_uobject_array()
+            .by_fqn(fqn)
+            .filter(|obj| {
+                let mut outers = successors(Some(*obj), |obj| obj.outer());
+                owner_path
+                    .iter()
+                    .all(|name| outers.next().is_some_and(|obj| obj.name() == *name))
+                    && outers.next().is_none()
+            })?;
+
+        successors(owner.cast::<UClass>().child_properties(), |field| {
+            field.next
+        })
+        .find(|field| field.name == *name)
+    }
+}
+
+#[repr(C)]
+pub struct TFieldPath<T> {
+    path: FFieldPath,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TFieldPath<T> {
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<T>> {
+        self.path.get().map(|p| p.cast())
+    }
+}
+
+impl<T> Deref for TFieldPath<T> {
+    type Target = FFieldPath;
+
+    fn deref(&self) -> &Self::Target {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FField, FFieldPath, FProperty};
+    use std::mem::{offset_of, size_of};
+
+    #[test]
+    fn test_field_layout() {
+        assert_eq!(offset_of!(FField, name), 0x28);
+        assert_eq!(size_of::<FField>(), 0x38);
+        assert_eq!(offset_of!(FProperty, offset), 0x4C);
+        assert_eq!(size_of::<FFieldPath>(), 0x20);
+    }
+}
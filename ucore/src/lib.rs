@@ -11,11 +11,19 @@ mod utils;
 pub use utils::*;
 mod api;
 pub use api::*;
+mod containers;
+pub use containers::*;
 mod name;
 pub use name::*;
 mod context;
 pub use context::*;
 mod object;
 pub use object::*;
+mod pointers;
+pub use pointers::*;
+mod delegates;
+pub use delegates::*;
+mod fields;
+pub use fields::*;
 
 pub use once_cell::{sync::Lazy as SyncLazy, unsync::Lazy as UnsyncLazy};
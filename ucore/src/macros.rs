@@ -61,7 +61,9 @@ macro_rules! impl_process_event_fns {
                         use $crate::Cache;
 
                         unsafe {
-                            let function = (*$crate::DEFAULT_CACHE).lookup(&$crate::fqn!(#$fqn).hash());
+                            let function = (*$crate::DEFAULT_CACHE)
+                                .lookup(&$crate::fqn!(#$fqn).hash())
+                                .unwrap_or_else(|| panic!("Failed to find function {}", $fqn));
 
                             #[repr(C)]
                             struct Args {
@@ -87,7 +89,9 @@ macro_rules! impl_uobject_like {
             fn static_class() -> $crate::Ptr<$crate::UClass> {
                 use $crate::Cache;
 
-                let class = (*$crate::DEFAULT_CACHE).lookup(&$crate::fqn!(#$fqn).hash());
+                let class = (*$crate::DEFAULT_CACHE)
+                    .lookup(&$crate::fqn!(#$fqn).hash())
+                    .unwrap_or_else(|| panic!("Failed to find class {}", $fqn));
                 class.cast()
             }
         }
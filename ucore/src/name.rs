@@ -6,7 +6,7 @@ use std::{
     fmt::{self, Debug, Display},
     hash::Hasher,
     marker::PhantomData,
-    mem::size_of,
+    mem::{size_of, MaybeUninit},
     str::from_utf8_unchecked,
 };
 use twox_hash::{XxHash32, XxHash64};
@@ -18,8 +18,49 @@ const FNAME_BLOCK_OFFSETS: u32 = 1 << FNAME_BLOCK_OFFSET_BITS;
 // const FNAME_ENTRY_ID_BITS: u32 = FNAME_BLOCK_OFFSET_BITS + FNAME_MAX_BLOCK_BITS;
 // const FNAME_ENTRY_ID_MASK: u32 = (1 << FNAME_ENTRY_ID_BITS) - 1;
 
-const WIDE_BIT: usize = 0;
-const LEN_BIT: usize = 6;
+// FNumberedData { Id: [u8; 4], Number: [u8; 4] }
+#[cfg(feature = "outline_name_number")]
+const NUMBERED_DATA_SIZE: usize = 8;
+
+/// Layout of `FNamePool` and its entries, see [`GlobalContext::with_name_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FNameLayout {
+    /// Alignment of the entries, offsets inside of the ids are divided by it.
+    pub stride: usize,
+    /// Offset of `Blocks` in the pool, `CurrentBlock` and `CurrentByteCursor` go right before it.
+    pub blocks: usize,
+    /// Offset of `FNameEntryHeader` in the entry, `ComparisonId` goes first in case preserving builds.
+    pub header: usize,
+    pub wide_bit: u32,
+    pub len_bit: u32,
+}
+
+impl Default for FNameLayout {
+    fn default() -> Self {
+        #[cfg(feature = "case_preserving_name")]
+        let (stride, header) = (4, size_of::<FNameEntryId>());
+        #[cfg(not(feature = "case_preserving_name"))]
+        let (stride, header) = (2, 0);
+
+        Self {
+            stride,
+            blocks: 0x10,
+            header,
+            wide_bit: 0,
+            len_bit: 6,
+        }
+    }
+}
+
+impl FNameLayout {
+    /// Layout of the initialized context or the default one.
+    #[inline]
+    pub fn current() -> Self {
+        GlobalContext::try_get()
+            .map(|c| c.name_layout())
+            .unwrap_or_default()
+    }
+}
 
 #[repr(transparent)]
 pub struct FNameEntryHeader(u16);
@@ -27,12 +68,12 @@ pub struct FNameEntryHeader(u16);
 impl FNameEntryHeader {
     #[inline]
     pub fn is_wide(&self) -> bool {
-        (self.0 >> WIDE_BIT) & 1 != 0
+        (self.0 >> FNameLayout::current().wide_bit) & 1 != 0
     }
 
     #[inline]
     pub fn len(&self) -> usize {
-        (self.0 >> LEN_BIT) as usize
+        (self.0 >> FNameLayout::current().len_bit) as usize
     }
 
     #[inline]
@@ -68,6 +109,8 @@ impl FNameEntry {
     pub fn header(&self) -> &FNameEntryHeader {
         unsafe {
             (self as *const Self)
+                .cast::<u8>()
+                .add(FNameLayout::current().header)
                 .cast::<FNameEntryHeader>()
                 .as_ref()
                 .unwrap()
@@ -78,7 +121,7 @@ impl FNameEntry {
         unsafe {
             (self as *const Self)
                 .cast::<u8>()
-                .add(size_of::<FNameEntryHeader>())
+                .add(FNameLayout::current().header + size_of::<FNameEntryHeader>())
                 .cast::<FNameEntryData>()
                 .as_ref()
                 .unwrap()
@@ -91,6 +134,20 @@ impl FNameEntry {
         unaligned + (unaligned & 1)
     }
 
+    /// Size of the whole entry including the padding up to the next one.
+    fn stride_size(&self, layout: &FNameLayout) -> usize {
+        #[cfg(feature = "outline_name_number")]
+        let data = if self.header().is_empty() {
+            NUMBERED_DATA_SIZE
+        } else {
+            self.size_in_bytes()
+        };
+        #[cfg(not(feature = "outline_name_number"))]
+        let data = self.size_in_bytes();
+
+        (layout.header + size_of::<FNameEntryHeader>() + data).next_multiple_of(layout.stride)
+    }
+
     pub fn to_str(&self) -> Cow<str> {
         let (header, data) = (self.header(), self.data());
         let len = header.len();
@@ -153,7 +210,7 @@ impl PartialEq<str> for FNameEntry {
 
 pub type FNameEntryId = u32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct FNameEntryHandle {
     pub block: u32,
@@ -180,22 +237,41 @@ impl From<FNameEntryHandle> for FNameEntryId {
 pub struct FNamePool(PhantomData<()>);
 
 impl FNamePool {
-    #[allow(unreachable_code)]
-    pub fn iter(&self) -> impl Iterator<Item = (FNameEntry, FNameEntryHandle)> {
-        todo!();
+    /// Every entry that holds a name, numbered entries of outlined numbers are skipped.
+    pub fn iter(&self) -> FNamePoolIter<'_> {
+        self.iter_from(0, 0)
+    }
 
-        [].into_iter()
+    fn iter_from(&self, block: u32, offset: usize) -> FNamePoolIter<'_> {
+        let layout = FNameLayout::current();
+        let (current_block, cursor) = unsafe {
+            let pool = (self as *const Self).cast::<u8>().add(layout.blocks);
+            (
+                pool.sub(size_of::<u32>() * 2).cast::<u32>().read(),
+                pool.sub(size_of::<u32>()).cast::<u32>().read() as usize,
+            )
+        };
+
+        FNamePoolIter {
+            pool: self,
+            layout,
+            current_block,
+            cursor,
+            block,
+            offset,
+        }
     }
 
     pub fn resolve(&self, handle: impl Into<FNameEntryHandle>) -> &FNameEntry {
         let FNameEntryHandle { block, offset } = handle.into();
+        let layout = FNameLayout::current();
         unsafe {
             (self as *const Self)
                 .cast::<u8>()
-                .add(0x10 + size_of::<usize>() * block as usize)
+                .add(layout.blocks + size_of::<usize>() * block as usize)
                 .cast::<*const u8>()
                 .read()
-                .add(offset as usize * 2)
+                .add(offset as usize * layout.stride)
                 .cast::<FNameEntry>()
                 .as_ref()
                 .unwrap()
@@ -203,10 +279,69 @@ impl FNamePool {
     }
 }
 
-#[derive(Clone, Copy)]
+/// Walks the blocks of the pool up to the `CurrentByteCursor` of the current block.
+pub struct FNamePoolIter<'a> {
+    pool: &'a FNamePool,
+    layout: FNameLayout,
+    current_block: u32,
+    cursor: usize,
+    block: u32,
+    /// Byte offset inside of the block.
+    offset: usize,
+}
+
+impl FNamePoolIter<'_> {
+    /// Position of the next entry, entries after it can be iterated with `FNamePool::iter_from`.
+    fn position(&self) -> (u32, usize) {
+        (self.block, self.offset)
+    }
+}
+
+impl<'a> Iterator for FNamePoolIter<'a> {
+    type Item = (&'a FNameEntry, FNameEntryHandle);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let size = if self.block == self.current_block {
+                self.cursor
+            } else {
+                self.layout.stride * FNAME_BLOCK_OFFSETS as usize
+            };
+
+            if self.offset >= size {
+                if self.block >= self.current_block {
+                    return None;
+                }
+
+                self.block += 1;
+                self.offset = 0;
+                continue;
+            }
+
+            let handle = FNameEntryHandle {
+                block: self.block,
+                offset: (self.offset / self.layout.stride) as u32,
+            };
+            let entry = self.pool.resolve(handle);
+            self.offset += entry.stride_size(&self.layout);
+
+            if !entry.header().is_empty() {
+                return Some((entry, handle));
+            }
+        }
+    }
+}
+
+/// Layout depends on the engine build:
+/// * `case_preserving_name` feature adds `DisplayIndex` after the comparison index.
+/// * `outline_name_number` feature removes `Number`, numbered names have their own entries.
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
 pub struct FName {
     index: FNameEntryId,
+    #[cfg(feature = "case_preserving_name")]
+    display_index: FNameEntryId,
+    #[cfg(not(feature = "outline_name_number"))]
     number: u32,
 }
 
@@ -219,8 +354,14 @@ impl From<FName> for FNameEntryId {
 
 impl From<FNameEntryId> for FName {
     #[inline]
-    fn from(number: FNameEntryId) -> Self {
-        Self { number, index: 0 }
+    fn from(index: FNameEntryId) -> Self {
+        Self {
+            index,
+            #[cfg(feature = "case_preserving_name")]
+            display_index: index,
+            #[cfg(not(feature = "outline_name_number"))]
+            number: 0,
+        }
     }
 }
 
@@ -230,30 +371,55 @@ impl FName {
         self.index
     }
 
+    #[cfg(feature = "case_preserving_name")]
+    #[inline]
+    pub fn display_index(&self) -> FNameEntryId {
+        self.display_index
+    }
+
+    #[cfg(not(feature = "outline_name_number"))]
+    #[inline]
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Finds an existing name, the lookup is case sensitive.
     pub fn lookup(name: &str) -> Option<FName> {
-        let mut hasher = XxHash64::default();
-        hasher.write(name.as_bytes());
-        let result = hasher.finish();
+        let pool = GlobalContext::get().name_pool();
+        NAME_INDEX.lock().find(pool, name).map(FName::from)
+    }
 
-        let mut lock = DEFAULT_NAME_CACHE.lock();
+    /// Finds the name or adds it to the pool through the constructor of the game,
+    /// `None` if [`GlobalContext::with_fname_constructor`] wasn't used.
+    pub fn find_or_add(name: &str) -> Option<FName> {
+        const FNAME_ADD: u32 = 1;
 
-        let maybe_name = lock.get(&result);
-        if maybe_name.is_some() {
-            return maybe_name.copied();
+        if let Some(name) = Self::lookup(name) {
+            return Some(name);
+        }
+        if let Some(name) = NAME_INDEX.lock().constructed.get(name) {
+            return Some(*name);
         }
 
-        let entry: FNameEntryId = GlobalContext::get()
-            .name_pool()
-            .iter()
-            .find_map(|(entry, handle)| (entry == *name).then(|| handle.into()))?;
-        let name: FName = entry.into();
-        lock.insert(result, name);
-
-        Some(name)
+        let constructor = GlobalContext::get().fname_constructor()?;
+        let wide = name.encode_utf16().chain([0]).collect::<Vec<_>>();
+        let mut result = MaybeUninit::<FName>::uninit();
+        let result = unsafe {
+            constructor(result.as_mut_ptr(), wide.as_ptr(), FNAME_ADD);
+            result.assume_init()
+        };
+
+        // Constructed names can differ from the string, i.e. case or the number suffix.
+        NAME_INDEX
+            .lock()
+            .constructed
+            .insert(name.to_owned(), result);
+
+        Some(result)
     }
 
     pub fn flush_cache(&self) {
-        DEFAULT_NAME_CACHE.lock().clear();
+        *NAME_INDEX.lock() = NameIndex::default();
     }
 }
 
@@ -262,7 +428,54 @@ use parking_lot::Mutex;
 #[cfg(feature = "spin")]
 use spin::Mutex;
 
-static DEFAULT_NAME_CACHE: Lazy<Mutex<HashMap<u64, FName>>> = Lazy::new(Default::default);
+/// Reverse lookup of names, built on the first lookup and extended with the names
+/// that were added to the pool since then.
+#[derive(Default)]
+struct NameIndex {
+    ids: HashMap<u64, Vec<FNameEntryId>>,
+    /// Names returned by `FName::find_or_add` that aren't stored as is in the pool.
+    constructed: HashMap<String, FName>,
+    /// Position in the pool up to which the names are indexed.
+    block: u32,
+    offset: usize,
+}
+
+impl NameIndex {
+    fn hash(name: &str) -> u64 {
+        let mut hasher = XxHash64::default();
+        hasher.write(name.as_bytes());
+        hasher.finish()
+    }
+
+    fn update(&mut self, pool: &FNamePool) {
+        let mut iter = pool.iter_from(self.block, self.offset);
+        for (entry, handle) in iter.by_ref() {
+            self.ids
+                .entry(Self::hash(&entry.to_str()))
+                .or_default()
+                .push(handle.into());
+        }
+
+        (self.block, self.offset) = iter.position();
+    }
+
+    fn get(&self, pool: &FNamePool, name: &str) -> Option<FNameEntryId> {
+        self.ids
+            .get(&Self::hash(name))?
+            .iter()
+            .copied()
+            .find(|id| *pool.resolve(*id) == *name)
+    }
+
+    fn find(&mut self, pool: &FNamePool, name: &str) -> Option<FNameEntryId> {
+        self.get(pool, name).or_else(|| {
+            self.update(pool);
+            self.get(pool, name)
+        })
+    }
+}
+
+static NAME_INDEX: Lazy<Mutex<NameIndex>> = Lazy::new(Default::default);
 
 impl Display for FName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -280,3 +493,54 @@ impl Debug for FName {
         write!(f, "{self}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FNamePool, NameIndex};
+
+    fn write_entry(block: &mut [u8], offset: usize, name: &str) -> usize {
+        let header = (name.len() as u16) << 6;
+        block[offset..offset + 2].copy_from_slice(&header.to_le_bytes());
+        block[offset + 2..offset + 2 + name.len()].copy_from_slice(name.as_bytes());
+
+        (offset + 2 + name.len()).next_multiple_of(2)
+    }
+
+    #[test]
+    fn test_name_pool() {
+        let mut block = vec![0u8; 0x100];
+        let mut cursor = 0;
+        for name in ["None", "ByteProperty", "Actor"] {
+            cursor = write_entry(&mut block, cursor, name);
+        }
+
+        // Lock, CurrentBlock and CurrentByteCursor, Blocks.
+        let mut raw = [0, cursor << 32, block.as_ptr() as usize];
+        let pool = unsafe { &*raw.as_ptr().cast::<FNamePool>() };
+
+        let names = pool
+            .iter()
+            .map(|(entry, handle)| (entry.to_str().into_owned(), handle.offset))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("None".to_owned(), 0),
+                ("ByteProperty".to_owned(), 3),
+                ("Actor".to_owned(), 10)
+            ]
+        );
+
+        let mut index = NameIndex::default();
+        assert_eq!(index.find(pool, "Actor"), Some(10));
+        assert_eq!(index.find(pool, "Pawn"), None);
+
+        // Names added after the index was built are picked up on a miss.
+        let pawn = cursor as u32 / 2;
+        cursor = write_entry(&mut block, cursor, "Pawn");
+        raw[1] = cursor << 32;
+        let pool = unsafe { &*raw.as_ptr().cast::<FNamePool>() };
+        assert_eq!(index.find(pool, "Pawn"), Some(pawn));
+        assert_eq!(index.find(pool, "None"), Some(0));
+    }
+}
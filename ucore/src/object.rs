@@ -1,19 +1,78 @@
-use crate::{FName, GlobalContext, HashedFqn, Ptr};
+use crate::{FField, FName, FNamePool, GlobalContext, HashedFqn, Ptr};
 use bitflags::bitflags;
 use memflex::assert_size;
+use once_cell::sync::Lazy;
 use std::{
+    collections::HashMap,
     iter::successors,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
 
-pub struct FUObjectItem {
-    pub object: *mut (),
-    pub flags: u32,
-    pub root_index: i32,
-    pub serial: u32,
+/// Layout of `FUObjectItem`, see [`GlobalContext::with_object_item_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FUObjectItemLayout {
+    /// Size of the item, must match `[FUObjectItem]` section of the dumper config.
+    pub size: usize,
+    pub flags: usize,
+    pub serial: usize,
+}
+
+impl Default for FUObjectItemLayout {
+    fn default() -> Self {
+        Self {
+            size: 0x18,
+            flags: 0x8,
+            serial: 0x10,
+        }
+    }
+}
+
+impl FUObjectItemLayout {
+    /// Layout of the initialized context or the default one.
+    #[inline]
+    pub fn current() -> Self {
+        GlobalContext::try_get()
+            .map(|c| c.object_item_layout())
+            .unwrap_or_default()
+    }
+}
+
+/// Entry of the object array, the object pointer goes first and other fields are read
+/// through [`FUObjectItemLayout`].
+#[repr(C)]
+pub struct FUObjectItem(PhantomData<()>);
+
+impl FUObjectItem {
+    #[inline]
+    pub fn object(&self) -> *mut () {
+        unsafe { self.read(0) }
+    }
+
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        unsafe { self.read(FUObjectItemLayout::current().flags) }
+    }
+
+    #[inline]
+    pub fn internal_flags(&self) -> InternalObjectFlags {
+        InternalObjectFlags::from_bits_retain(self.flags())
+    }
+
+    #[inline]
+    pub fn serial(&self) -> u32 {
+        unsafe { self.read(FUObjectItemLayout::current().serial) }
+    }
+
+    unsafe fn read<T>(&self, offset: usize) -> T {
+        (self as *const Self)
+            .cast::<u8>()
+            .add(offset)
+            .cast::<T>()
+            .read()
+    }
 }
-assert_size!(FUObjectItem, 0x18);
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -32,45 +91,431 @@ impl FChunkedFixedUObjectArray {
     }
 
     pub fn nth(&self, idx: u32) -> Option<Ptr<UObject>> {
+        let item = self.item(idx)?;
+        let object = NonNull::new(item.object().cast())?;
+        Some(Ptr(object))
+    }
+
+    #[inline]
+    pub fn item(&self, idx: u32) -> Option<&FUObjectItem> {
+        self.item_with(idx, FUObjectItemLayout::current().size)
+    }
+
+    fn item_with(&self, idx: u32, item_size: usize) -> Option<&FUObjectItem> {
         const NUM_ELEMS_PER_CHUNK: usize = 64 * 1024;
 
+        if idx >= self.num_elems {
+            return None;
+        }
+
         let chunk_idx = idx as usize / NUM_ELEMS_PER_CHUNK;
-        let array = GlobalContext::get().chunked_fixed_uobject_array();
-        let item = unsafe {
-            array
-                .objects
+        unsafe {
+            self.objects
                 .add(chunk_idx)
                 .read()
-                .add(idx as usize % NUM_ELEMS_PER_CHUNK)
-        };
-        let object = NonNull::new(unsafe { (*item).object.cast() })?;
-        Some(Ptr(object))
+                .cast::<u8>()
+                .add(idx as usize % NUM_ELEMS_PER_CHUNK * item_size)
+                .cast::<FUObjectItem>()
+                .as_ref()
+        }
     }
 
+    /// Looks up an object through the object index, new slots are indexed on a miss.
     pub fn by_fqn(&self, hash: HashedFqn) -> Option<Ptr<UObject>> {
-        GlobalContext::get()
-            .chunked_fixed_uobject_array()
+        let names = GlobalContext::get().name_pool();
+        OBJECT_INDEX.lock().find(self, names, hash)
+    }
+
+    /// Objects whose class is exactly `class`, instances of subclasses are not included.
+    pub fn by_class(&self, class: Ptr<UClass>) -> Vec<Ptr<UObject>> {
+        let names = GlobalContext::get().name_pool();
+        let mut index = OBJECT_INDEX.lock();
+        index.update(self, names);
+        index.instances(self, class).collect()
+    }
+
+    /// Instances of `T` and its subclasses, see [`Instances`] for filters.
+    pub fn instances_of<T: UObjectLike>(&self) -> Instances<T> {
+        // Class is looked up before locking, it goes through the index too.
+        let class = T::static_class();
+        let names = GlobalContext::get().name_pool();
+        let mut index = OBJECT_INDEX.lock();
+        index.update(self, names);
+
+        Instances::new(index.subclass_instances(self, class))
+    }
+
+    /// Indexes every slot again, i.e. after objects were destroyed and their slots reused.
+    /// Lookups only index slots added since the last update.
+    pub fn revalidate_index(&self) {
+        let names = GlobalContext::get().name_pool();
+        OBJECT_INDEX.lock().revalidate(self, names);
+    }
+
+    /// Drops the object index, it is rebuilt on the next lookup.
+    pub fn flush_index(&self) {
+        *OBJECT_INDEX.lock() = ObjectIndex::default();
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+use parking_lot::Mutex;
+#[cfg(feature = "spin")]
+use spin::Mutex;
+
+struct IndexedObject {
+    object: Ptr<UObject>,
+    class: Ptr<UClass>,
+    fqn: Option<HashedFqn>,
+}
+
+/// Objects by their full name and by their class. Lookups only index slots added since
+/// the last update, objects of reused slots are picked up by [`ObjectIndex::revalidate`].
+#[derive(Default)]
+struct ObjectIndex {
+    slots: Vec<Option<IndexedObject>>,
+    fqns: HashMap<HashedFqn, Vec<u32>>,
+    instances: HashMap<Ptr<UClass>, Vec<u32>>,
+}
+
+impl ObjectIndex {
+    fn update(&mut self, objects: &FChunkedFixedUObjectArray, names: &FNamePool) {
+        self.truncate(objects.num_elems);
+        for idx in self.slots.len() as u32..objects.num_elems {
+            self.slots.push(None);
+            self.index(objects, names, idx);
+        }
+    }
+
+    /// Indexes every slot, slots that still hold the same object are kept.
+    fn revalidate(&mut self, objects: &FChunkedFixedUObjectArray, names: &FNamePool) {
+        self.truncate(objects.num_elems);
+        self.slots.resize_with(objects.num_elems as usize, || None);
+        for idx in 0..objects.num_elems {
+            self.index(objects, names, idx);
+        }
+    }
+
+    fn truncate(&mut self, len: u32) {
+        for idx in len..self.slots.len() as u32 {
+            self.unlink(idx);
+        }
+        self.slots.truncate(len as usize);
+    }
+
+    fn index(&mut self, objects: &FChunkedFixedUObjectArray, names: &FNamePool, idx: u32) {
+        let object = objects.nth(idx);
+        if self.slots[idx as usize].as_ref().map(|slot| slot.object) == object {
+            return;
+        }
+
+        self.unlink(idx);
+        let Some(object) = object else {
+            return;
+        };
+        let indexed = IndexedObject {
+            object,
+            class: object.class,
+            fqn: object.hashed_fqn_in(names),
+        };
+        if let Some(fqn) = indexed.fqn {
+            self.fqns.entry(fqn).or_default().push(idx);
+        }
+        self.instances.entry(indexed.class).or_default().push(idx);
+        self.slots[idx as usize] = Some(indexed);
+    }
+
+    // Empties the slot and removes it from the lookup tables.
+    fn unlink(&mut self, idx: u32) {
+        let Some(old) = self.slots[idx as usize].take() else {
+            return;
+        };
+        if let Some(ids) = old.fqn.and_then(|fqn| self.fqns.get_mut(&fqn)) {
+            ids.retain(|id| *id != idx);
+        }
+        if let Some(ids) = self.instances.get_mut(&old.class) {
+            ids.retain(|id| *id != idx);
+        }
+    }
+
+    /// Object in the slot, if it wasn't replaced since the slot was indexed.
+    fn object(&self, objects: &FChunkedFixedUObjectArray, idx: u32) -> Option<Ptr<UObject>> {
+        let object = objects.nth(idx)?;
+        let slot = self.slots.get(idx as usize)?.as_ref()?;
+        (slot.object == object).then_some(object)
+    }
+
+    fn get(&self, objects: &FChunkedFixedUObjectArray, hash: HashedFqn) -> Option<Ptr<UObject>> {
+        self.fqns
+            .get(&hash)?
             .iter()
-            .find(|obj| obj.eq_fqn(hash))
+            .find_map(|idx| self.object(objects, *idx))
+    }
+
+    fn find(
+        &mut self,
+        objects: &FChunkedFixedUObjectArray,
+        names: &FNamePool,
+        hash: HashedFqn,
+    ) -> Option<Ptr<UObject>> {
+        self.get(objects, hash).or_else(|| {
+            self.update(objects, names);
+            self.get(objects, hash)
+        })
+    }
+
+    fn instances<'a>(
+        &'a self,
+        objects: &'a FChunkedFixedUObjectArray,
+        class: Ptr<UClass>,
+    ) -> impl Iterator<Item = Ptr<UObject>> + 'a {
+        self.instances
+            .get(&class)
+            .into_iter()
+            .flatten()
+            .filter_map(|idx| self.object(objects, *idx))
+    }
+
+    /// Instances of `class` and its subclasses, ordered by their index in the object array.
+    fn subclass_instances(
+        &self,
+        objects: &FChunkedFixedUObjectArray,
+        class: Ptr<UClass>,
+    ) -> Vec<(Ptr<UObject>, Ptr<FUObjectItem>)> {
+        let mut ids = self
+            .instances
+            .iter()
+            .filter(|(instance_class, _)| instance_class.is(class))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        ids.into_iter()
+            .filter_map(|idx| {
+                let object = self.object(objects, idx)?;
+                Some((object, Ptr::from_ref(objects.item(idx)?)))
+            })
+            .collect()
     }
 }
 
-#[allow(dead_code)]
+/// Iterator over the instances of a type, everything is included unless skipped.
+/// Flags of objects and their `FUObjectItem`s are checked while iterating.
+pub struct Instances<T> {
+    objects: std::vec::IntoIter<(Ptr<UObject>, Ptr<FUObjectItem>)>,
+    skip: ObjectFlags,
+    skip_internal: InternalObjectFlags,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Instances<T> {
+    fn new(objects: Vec<(Ptr<UObject>, Ptr<FUObjectItem>)>) -> Self {
+        Self {
+            objects: objects.into_iter(),
+            skip: ObjectFlags::empty(),
+            skip_internal: InternalObjectFlags::empty(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn skip_defaults(mut self) -> Self {
+        self.skip |= ObjectFlags::ClassDefaultObject;
+        self
+    }
+
+    pub fn skip_archetypes(mut self) -> Self {
+        self.skip |= ObjectFlags::ArchetypeObject;
+        self
+    }
+
+    pub fn skip_garbage(mut self) -> Self {
+        self.skip |= ObjectFlags::PendingKill | ObjectFlags::Garbage;
+        self.skip_internal |= InternalObjectFlags::PendingKill | InternalObjectFlags::Garbage;
+        self
+    }
+
+    /// Only objects that exist in the game world, i.e. all of the above are skipped.
+    pub fn live(self) -> Self {
+        self.skip_defaults().skip_archetypes().skip_garbage()
+    }
+}
+
+impl<T: UObjectLike> Iterator for Instances<T> {
+    type Item = Ptr<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.objects
+            .by_ref()
+            .find(|(obj, item)| {
+                !obj.flags.intersects(self.skip)
+                    && !item.internal_flags().intersects(self.skip_internal)
+            })
+            .map(|(obj, _)| obj.cast())
+    }
+}
+
+static OBJECT_INDEX: Lazy<Mutex<ObjectIndex>> = Lazy::new(Default::default);
+
+/// Finds an object by a path like `Engine.Actor`, `None` if it doesn't exist or isn't a `T`.
+pub fn find_object<T: UObjectLike>(path: &str) -> Option<Ptr<T>> {
+    let object = UObject::get_by_fqn(HashedFqn::from_human_readable(path)?)?;
+    T::from_uobject(object)
+}
+
+/// Instances of `T` in the global object array, e.g. `instances_of::<APlayerController>().live()`.
+#[inline]
+pub fn instances_of<T: UObjectLike>() -> Instances<T> {
+    GlobalContext::get()
+        .chunked_fixed_uobject_array()
+        .instances_of()
+}
+
+/// Reference to an object that is checked against its slot in the object array on every access.
+///
+/// Serial numbers are only assigned by the engine once a weak pointer to the object was made,
+/// until then the handle can only tell that a slot was reused if the object pointer changed.
+pub struct ObjectHandle<T> {
+    index: u32,
+    serial: u32,
+    object: Ptr<T>,
+}
+
+impl<T> Clone for ObjectHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ObjectHandle<T> {}
+
+impl<T> ObjectHandle<T> {
+    /// `None` if the object isn't stored in the global object array.
+    pub fn new(object: Ptr<T>) -> Option<Self> {
+        Self::new_in(GlobalContext::get().chunked_fixed_uobject_array(), object)
+    }
+
+    fn new_in(objects: &FChunkedFixedUObjectArray, object: Ptr<T>) -> Option<Self> {
+        let index = object.cast::<UObject>().index;
+        let item = objects.item(index)?;
+        (item.object() == object.as_ptr().cast()).then_some(Self {
+            index,
+            serial: item.serial(),
+            object,
+        })
+    }
+
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    /// Object, unless it was collected or is pending kill.
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<T>> {
+        self.get_in(GlobalContext::get().chunked_fixed_uobject_array())
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Object still exists, but is marked for destruction.
+    #[inline]
+    pub fn is_pending_kill(&self) -> bool {
+        self.is_pending_kill_in(GlobalContext::get().chunked_fixed_uobject_array())
+    }
+
+    // Serial is assigned later if the handle was made before the engine needed one,
+    // only the pointer can be compared in this case.
+    fn item<'a>(&self, objects: &'a FChunkedFixedUObjectArray) -> Option<&'a FUObjectItem> {
+        let item = objects.item(self.index)?;
+        (item.object() == self.object.as_ptr().cast()
+            && (self.serial == 0 || item.serial() == self.serial))
+            .then_some(item)
+    }
+
+    fn get_in(&self, objects: &FChunkedFixedUObjectArray) -> Option<Ptr<T>> {
+        self.item(objects)?;
+        (!self.is_pending_kill_in(objects)).then_some(self.object)
+    }
+
+    fn is_pending_kill_in(&self, objects: &FChunkedFixedUObjectArray) -> bool {
+        const GARBAGE: InternalObjectFlags =
+            InternalObjectFlags::PendingKill.union(InternalObjectFlags::Garbage);
+
+        self.item(objects).is_some_and(|item| {
+            item.internal_flags().intersects(GARBAGE)
+                || self
+                    .object
+                    .cast::<UObject>()
+                    .flags
+                    .intersects(ObjectFlags::PendingKill | ObjectFlags::Garbage)
+        })
+    }
+}
+
+/// Offsets of `UStruct` fields, see [`GlobalContext::with_ustruct_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UStructLayout {
+    pub super_struct: usize,
+    /// `None` in UProperty builds, their properties are linked into `Children` instead.
+    pub child_properties: Option<usize>,
+}
+
+impl Default for UStructLayout {
+    fn default() -> Self {
+        Self {
+            super_struct: 0x40,
+            child_properties: Some(0x50),
+        }
+    }
+}
+
+impl UStructLayout {
+    /// Layout of the initialized context or the default one.
+    #[inline]
+    pub fn current() -> Self {
+        GlobalContext::try_get()
+            .map(|c| c.ustruct_layout())
+            .unwrap_or_default()
+    }
+}
+
+/// Fields after the `UObject` are read through [`UStructLayout`].
+#[repr(C)]
 pub struct UClass {
     object: UObject,
-    next: *const (),
-    _pad_0x40: [u8; 0x10],
-    super_struct: Option<Ptr<Self>>,
 }
 
 impl UClass {
     pub fn is(&self, class: Ptr<Self>) -> bool {
-        successors(Some(Ptr::from_ref(self)), |class| class.super_struct).any(|ptr| ptr == class)
+        successors(Some(Ptr::from_ref(self)), |class| class.super_struct()).any(|ptr| ptr == class)
     }
 
     #[inline]
     pub fn super_struct(&self) -> Option<Ptr<Self>> {
-        self.super_struct
+        unsafe { self.read(UStructLayout::current().super_struct) }
+    }
+
+    /// First property declared in this struct, parent properties are not included.
+    #[inline]
+    pub fn child_properties(&self) -> Option<Ptr<FField>> {
+        let offset = UStructLayout::current().child_properties?;
+        unsafe { self.read(offset) }
+    }
+
+    unsafe fn read<T>(&self, offset: usize) -> T {
+        (self as *const Self)
+            .cast::<u8>()
+            .add(offset)
+            .cast::<T>()
+            .read()
     }
 }
 
@@ -179,7 +624,10 @@ pub struct UObject {
     name: FName,
     outer: Option<Ptr<Self>>,
 }
+#[cfg(not(all(feature = "case_preserving_name", not(feature = "outline_name_number"))))]
 assert_size!(UObject, 0x28);
+#[cfg(all(feature = "case_preserving_name", not(feature = "outline_name_number")))]
+assert_size!(UObject, 0x30);
 
 unsafe impl Send for UObject {}
 unsafe impl Sync for UObject {}
@@ -217,12 +665,35 @@ impl UObject {
         self.name
     }
 
+    #[inline]
     pub fn eq_fqn(&self, hash: HashedFqn) -> bool {
-        let namepool = GlobalContext::get().name_pool();
-        successors(Some(Ptr::from_ref(self)), |obj| obj.outer)
-            .map(|obj| namepool.resolve(obj.name.index()))
-            .enumerate()
-            .all(|(i, entry)| hash.0[i] == entry.hash())
+        self.hashed_fqn() == Some(hash)
+    }
+
+    /// `None` if the object is nested deeper than an `Fqn` can describe.
+    #[inline]
+    pub fn hashed_fqn(&self) -> Option<HashedFqn> {
+        self.hashed_fqn_in(GlobalContext::get().name_pool())
+    }
+
+    fn hashed_fqn_in(&self, names: &FNamePool) -> Option<HashedFqn> {
+        let path = successors(Some(Ptr::from_ref(self)), |obj| obj.outer).map(|obj| obj.name);
+        HashedFqn::from_names(names, path)
+    }
+
+    /// Looks up a `UFunction` by name in the class hierarchy of the object.
+    pub fn find_function(&self, name: FName) -> Option<Ptr<Self>> {
+        let context = GlobalContext::get();
+        let (objects, names) = (context.chunked_fixed_uobject_array(), context.name_pool());
+        successors(Some(self.class), |class| class.super_struct()).find_map(|class| {
+            let class = class.cast::<Self>();
+            let path = [name]
+                .into_iter()
+                .chain(successors(Some(class), |obj| obj.outer).map(|obj| obj.name));
+            objects
+                .by_fqn(HashedFqn::from_names(names, path)?)
+                .filter(|obj| obj.outer == Some(class) && obj.name == name)
+        })
     }
 
     /// # Safety
@@ -278,3 +749,291 @@ bitflags! {
         const AllocatedInSharedPage	= 0x80000000;
     }
 }
+
+bitflags! {
+    /// Flags of `FUObjectItem`, `Garbage` replaced `PendingKill` in UE5.
+    #[derive(Debug, Clone, Copy)]
+    pub struct InternalObjectFlags : u32 {
+        const None = 0;
+        const LoaderImport = 1 << 20;
+        const Garbage = 1 << 21;
+        const ReachableInCluster = 1 << 23;
+        const ClusterRoot = 1 << 24;
+        const Native = 1 << 25;
+        const Async = 1 << 26;
+        const AsyncLoading = 1 << 27;
+        const Unreachable = 1 << 28;
+        const PendingKill = 1 << 29;
+        const RootSet = 1 << 30;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FChunkedFixedUObjectArray, Instances, InternalObjectFlags, ObjectFlags, ObjectHandle,
+        ObjectIndex, UClass, UObject,
+    };
+    use crate::{fqn, FField, FName, FNamePool, Ptr, UObjectLike};
+    use std::{
+        mem::size_of,
+        ptr::{null, null_mut, NonNull},
+    };
+
+    /// "Engine", "Actor" and "Pawn" at offsets 0, 4 and 8.
+    fn name_block() -> [u8; 0x20] {
+        let mut block = [0u8; 0x20];
+        for (offset, name) in [(0, "Engine"), (8, "Actor"), (16, "Pawn")] {
+            let header = (name.len() as u16) << 6;
+            block[offset..offset + 2].copy_from_slice(&header.to_le_bytes());
+            block[offset + 2..offset + 2 + name.len()].copy_from_slice(name.as_bytes());
+        }
+        block
+    }
+
+    /// `FUObjectItem` with the default layout.
+    #[repr(C)]
+    struct Item {
+        object: *mut (),
+        flags: u32,
+        root_index: i32,
+        serial: u32,
+    }
+
+    fn object_array<T>(chunks: &[*const T], num_elems: u32) -> FChunkedFixedUObjectArray {
+        FChunkedFixedUObjectArray {
+            objects: chunks.as_ptr().cast(),
+            preallocated: null(),
+            max_elems: num_elems,
+            num_elems,
+            max_chunks: 1,
+            num_chunks: 1,
+        }
+    }
+
+    fn object(name: u32, class: Ptr<UObject>, outer: Option<&UObject>) -> UObject {
+        UObject {
+            vmt: null(),
+            flags: ObjectFlags::NoFlags,
+            index: 0,
+            class: class.cast(),
+            name: FName::from(name),
+            outer: outer.map(Ptr::from_ref),
+        }
+    }
+
+    fn item(object: &UObject) -> Item {
+        Item {
+            object: Ptr::from_ref(object).as_ptr().cast(),
+            flags: 0,
+            root_index: 0,
+            serial: 0,
+        }
+    }
+
+    #[test]
+    fn test_object_index() {
+        let block = name_block();
+        let raw = [0, 0x20 << 32, block.as_ptr() as usize];
+        let names = unsafe { &*raw.as_ptr().cast::<FNamePool>() };
+
+        let class = Ptr(NonNull::<UObject>::dangling());
+        let engine = object(0, class, None);
+        let actor = object(4, class, Some(&engine));
+        let pawn = object(8, class, Some(&engine));
+
+        let mut items = [item(&engine), item(&actor), item(&pawn)];
+        let chunks = [items.as_mut_ptr().cast_const()];
+        let mut objects = object_array(&chunks, 2);
+
+        let mut index = ObjectIndex::default();
+        let found = index.find(&objects, names, fqn!(Engine.Actor).hash());
+        assert_eq!(found, Some(Ptr::from_ref(&actor)));
+        assert_eq!(index.find(&objects, names, fqn!(Engine.Pawn).hash()), None);
+        assert_eq!(
+            index.find(&objects, names, fqn!(Foo.Engine.Actor).hash()),
+            None
+        );
+
+        // Objects added after the index was built are picked up on a miss.
+        objects.num_elems = 3;
+        let found = index.find(&objects, names, fqn!(Engine.Pawn).hash());
+        assert_eq!(found, Some(Ptr::from_ref(&pawn)));
+        assert_eq!(index.instances(&objects, class.cast()).count(), 3);
+
+        // Freed slots are not returned, even before the index is refreshed.
+        unsafe { (*chunks[0].cast_mut().add(1)).object = null_mut() };
+        assert_eq!(index.find(&objects, names, fqn!(Engine.Actor).hash()), None);
+        assert_eq!(index.instances(&objects, class.cast()).count(), 2);
+
+        // Reused slots are only picked up once the index is revalidated.
+        let nested = object(4, class, Some(&pawn));
+        unsafe { (*chunks[0].cast_mut().add(1)).object = Ptr::from_ref(&nested).as_ptr().cast() };
+        let hash = fqn!(Engine.Pawn.Actor).hash();
+        assert_eq!(index.find(&objects, names, hash), None);
+        index.revalidate(&objects, names);
+        assert_eq!(
+            index.find(&objects, names, hash),
+            Some(Ptr::from_ref(&nested))
+        );
+
+        // Slots past the end of a shrunk array are purged.
+        objects.num_elems = 1;
+        index.update(&objects, names);
+        assert_eq!(index.slots.len(), 1);
+        assert!(index
+            .fqns
+            .values()
+            .chain(index.instances.values())
+            .flatten()
+            .all(|id| *id == 0));
+    }
+
+    /// `UClass` with the default layout.
+    #[repr(C)]
+    struct Class {
+        object: UObject,
+        _pad_0x28: [u8; 0x18],
+        super_struct: Option<Ptr<Class>>,
+        _pad_0x48: [u8; 0x8],
+        child_properties: Option<Ptr<FField>>,
+    }
+
+    fn class(name: u32, super_struct: Option<&Class>) -> Class {
+        Class {
+            object: object(name, Ptr(NonNull::dangling()), None),
+            _pad_0x28: [0; 0x18],
+            super_struct: super_struct.map(Ptr::from_ref),
+            _pad_0x48: [0; 0x8],
+            child_properties: None,
+        }
+    }
+
+    struct Actor;
+
+    unsafe impl UObjectLike for Actor {
+        fn static_class() -> Ptr<UClass> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_instances() {
+        let block = name_block();
+        let raw = [0, 0x20 << 32, block.as_ptr() as usize];
+        let names = unsafe { &*raw.as_ptr().cast::<FNamePool>() };
+
+        let actor_class = class(4, None);
+        let pawn_class = class(8, Some(&actor_class));
+        let engine = object(0, Ptr::from_ref(&actor_class).cast(), None);
+        let actor = object(4, Ptr::from_ref(&actor_class).cast(), Some(&engine));
+        let mut pawn_default = object(8, Ptr::from_ref(&pawn_class).cast(), Some(&engine));
+        pawn_default.flags = ObjectFlags::ClassDefaultObject;
+        let mut pawn = object(8, Ptr::from_ref(&pawn_class).cast(), Some(&engine));
+        pawn.flags = ObjectFlags::Garbage;
+        // Only the object item is marked, flags of the object are left intact.
+        let killed = object(8, Ptr::from_ref(&pawn_class).cast(), Some(&engine));
+
+        let mut items = [
+            item(&actor),
+            item(&pawn_default),
+            item(&pawn),
+            item(&killed),
+        ];
+        items[3].flags = InternalObjectFlags::PendingKill.bits();
+        let chunks = [items.as_mut_ptr().cast_const()];
+        let objects = object_array(&chunks, 4);
+
+        let mut index = ObjectIndex::default();
+        index.update(&objects, names);
+        let instances = |class: &Class| {
+            let class = Ptr::from_ref(class).cast();
+            Instances::<Actor>::new(index.subclass_instances(&objects, class))
+        };
+
+        let all = instances(&actor_class).collect::<Vec<_>>();
+        let expected = [&actor, &pawn_default, &pawn, &killed].map(|obj| Ptr::from_ref(obj).cast());
+        assert_eq!(all, expected);
+        assert_eq!(instances(&pawn_class).count(), 3);
+        assert_eq!(instances(&pawn_class).skip_defaults().count(), 2);
+        assert_eq!(instances(&pawn_class).skip_garbage().count(), 1);
+        assert_eq!(instances(&actor_class).live().count(), 1);
+    }
+
+    #[test]
+    fn test_object_handle() {
+        let class = Ptr(NonNull::<UObject>::dangling());
+        let engine = object(0, class, None);
+        let mut actor = object(4, class, Some(&engine));
+        actor.index = 1;
+
+        let mut items = [item(&engine), item(&actor)];
+        items[1].serial = 5;
+        let slot = items.as_mut_ptr().wrapping_add(1);
+        let chunks = [items.as_mut_ptr().cast_const()];
+        let objects = object_array(&chunks, 2);
+
+        // Object claims to be at the slot of the actor.
+        let mut stray = object(8, class, Some(&engine));
+        stray.index = 1;
+        assert!(ObjectHandle::new_in(&objects, Ptr::from_ref(&stray)).is_none());
+
+        let handle = ObjectHandle::new_in(&objects, Ptr::from_ref(&actor)).unwrap();
+        assert_eq!(handle.serial(), 5);
+        assert_eq!(handle.get_in(&objects), Some(Ptr::from_ref(&actor)));
+
+        unsafe { (*slot).flags = InternalObjectFlags::PendingKill.bits() };
+        assert!(handle.is_pending_kill_in(&objects));
+        assert_eq!(handle.get_in(&objects), None);
+
+        // Slot was reused for another object.
+        unsafe {
+            *slot = Item {
+                serial: 6,
+                ..item(&stray)
+            }
+        };
+        assert!(!handle.is_pending_kill_in(&objects));
+        assert_eq!(handle.get_in(&objects), None);
+
+        // Serial is assigned after the handle was made.
+        unsafe { *slot = item(&actor) };
+        let handle = ObjectHandle::new_in(&objects, Ptr::from_ref(&actor)).unwrap();
+        assert_eq!(handle.serial(), 0);
+        unsafe { (*slot).serial = 7 };
+        assert_eq!(handle.get_in(&objects), Some(Ptr::from_ref(&actor)));
+
+        unsafe {
+            *slot = Item {
+                serial: 8,
+                ..item(&stray)
+            }
+        };
+        assert_eq!(handle.get_in(&objects), None);
+    }
+
+    #[test]
+    fn test_item_stride() {
+        #[repr(C)]
+        struct StatItem {
+            item: Item,
+            stat_id: usize,
+        }
+
+        let class = Ptr(NonNull::<UObject>::dangling());
+        let engine = object(0, class, None);
+        let actor = object(4, class, Some(&engine));
+
+        let mut items = [&engine, &actor].map(|obj| StatItem {
+            item: item(obj),
+            stat_id: 0,
+        });
+        items[1].item.serial = 5;
+        let chunks = [items.as_ptr()];
+        let objects = object_array(&chunks, 2);
+
+        let actor_item = objects.item_with(1, size_of::<StatItem>()).unwrap();
+        assert_eq!(actor_item.object(), Ptr::from_ref(&actor).as_ptr().cast());
+        assert_eq!(actor_item.serial(), 5);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{FName, FString, GlobalContext, Ptr, UClass, UObject};
+use memflex::assert_size;
+use std::{fmt, marker::PhantomData, ptr::NonNull};
+
+/// Object reference that doesn't keep the object alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct FWeakObjectPtr {
+    index: i32,
+    serial: i32,
+}
+assert_size!(FWeakObjectPtr, 0x8);
+
+impl FWeakObjectPtr {
+    /// Returns the object only if its slot in `GObjects` still has the same serial number.
+    pub fn get(&self) -> Option<Ptr<UObject>> {
+        if self.index < 0 || self.serial == 0 {
+            return None;
+        }
+
+        let item = GlobalContext::get()
+            .chunked_fixed_uobject_array()
+            .item(self.index as u32)?;
+        if item.serial() != self.serial as u32 {
+            return None;
+        }
+
+        NonNull::new(item.object().cast()).map(Ptr)
+    }
+
+    #[inline]
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn serial(&self) -> i32 {
+        self.serial
+    }
+}
+
+#[repr(C)]
+pub struct TWeakObjectPtr<T> {
+    weak: FWeakObjectPtr,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TWeakObjectPtr<T> {
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<T>> {
+        self.weak.get().map(|p| p.cast())
+    }
+
+    #[inline]
+    pub fn weak(&self) -> &FWeakObjectPtr {
+        &self.weak
+    }
+}
+
+/// Weak pointer with an identifier that can be used to load the object.
+#[repr(C)]
+pub struct TPersistentObjectPtr<Id> {
+    weak: FWeakObjectPtr,
+    tag: i32,
+    id: Id,
+}
+
+impl<Id> TPersistentObjectPtr<Id> {
+    /// Object is only available if it's loaded.
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<UObject>> {
+        self.weak.get()
+    }
+
+    #[inline]
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+}
+
+#[repr(C)]
+pub struct FSoftObjectPath {
+    pub asset_path_name: FName,
+    pub sub_path_string: FString,
+}
+
+impl fmt::Debug for FSoftObjectPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.asset_path_name)?;
+        if !self.sub_path_string.is_empty() {
+            write!(f, ":{}", self.sub_path_string)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+pub struct TSoftObjectPtr<T> {
+    ptr: TPersistentObjectPtr<FSoftObjectPath>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TSoftObjectPtr<T> {
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<T>> {
+        self.ptr.get().map(|p| p.cast())
+    }
+
+    #[inline]
+    pub fn path(&self) -> &FSoftObjectPath {
+        self.ptr.id()
+    }
+}
+
+/// Same as [`TSoftObjectPtr`], `T` is the base class of the referenced class.
+#[repr(C)]
+pub struct TSoftClassPtr<T> {
+    ptr: TPersistentObjectPtr<FSoftObjectPath>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TSoftClassPtr<T> {
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<UClass>> {
+        self.ptr.get().map(|p| p.cast())
+    }
+
+    #[inline]
+    pub fn path(&self) -> &FSoftObjectPath {
+        self.ptr.id()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct FUniqueObjectGuid {
+    pub guid: [u32; 4],
+}
+
+#[repr(C)]
+pub struct TLazyObjectPtr<T> {
+    ptr: TPersistentObjectPtr<FUniqueObjectGuid>,
+    _marker: PhantomData<T>,
+}
+assert_size!(TLazyObjectPtr<()>, 0x1C);
+
+impl<T> TLazyObjectPtr<T> {
+    #[inline]
+    pub fn get(&self) -> Option<Ptr<T>> {
+        self.ptr.get().map(|p| p.cast())
+    }
+
+    #[inline]
+    pub fn guid(&self) -> &FUniqueObjectGuid {
+        self.ptr.id()
+    }
+}
+
+/// Object implementing interface `T`.
+#[repr(C)]
+pub struct TScriptInterface<T> {
+    object: Option<Ptr<UObject>>,
+    interface: Option<NonNull<()>>,
+    _marker: PhantomData<T>,
+}
+assert_size!(TScriptInterface<()>, 0x10);
+
+impl<T> TScriptInterface<T> {
+    #[inline]
+    pub fn object(&self) -> Option<Ptr<UObject>> {
+        self.object
+    }
+
+    /// Pointer to the interface vtable inside of the object.
+    #[inline]
+    pub fn interface(&self) -> Option<NonNull<()>> {
+        self.interface
+    }
+}
@@ -1,6 +1,7 @@
+use crate::{FName, FNamePool};
 use std::{
     fmt::{self, Debug},
-    hash::Hasher,
+    hash::{Hash, Hasher},
     marker::PhantomData,
     mem::{transmute, MaybeUninit},
     ops::{Deref, DerefMut},
@@ -41,6 +42,12 @@ impl<T: ?Sized> PartialEq for Ptr<T> {
     }
 }
 
+impl<T: ?Sized> Hash for Ptr<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().cast::<()>().hash(state)
+    }
+}
+
 impl<T: ?Sized> Ptr<T> {
     pub fn cast<U>(self) -> Ptr<U> {
         Ptr(self.0.cast::<U>())
@@ -97,6 +104,37 @@ const FQN_LEN: usize = 4;
 #[repr(transparent)]
 pub struct HashedFqn(pub(crate) [u32; FQN_LEN]);
 
+impl HashedFqn {
+    /// Hashes a path like `Engine.Actor`, `None` if it has too many parts.
+    pub fn from_human_readable(path: &str) -> Option<Self> {
+        let mut out = [0; FQN_LEN];
+        for (i, part) in path.rsplit('.').enumerate() {
+            *out.get_mut(i)? = Self::hash_part(part);
+        }
+
+        Some(Self(out))
+    }
+
+    /// Hashes a path of names, the innermost name goes first.
+    pub(crate) fn from_names(
+        names: &FNamePool,
+        path: impl IntoIterator<Item = FName>,
+    ) -> Option<Self> {
+        let mut out = [0; FQN_LEN];
+        for (i, name) in path.into_iter().enumerate() {
+            *out.get_mut(i)? = names.resolve(name.index()).hash();
+        }
+
+        Some(Self(out))
+    }
+
+    fn hash_part(part: &str) -> u32 {
+        let mut hasher = XxHash32::default();
+        hasher.write(part.as_bytes());
+        hasher.finish() as u32
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Fqn {
     parts: [&'static str; FQN_LEN],
@@ -138,9 +176,7 @@ impl Fqn {
     pub fn hash(&self) -> HashedFqn {
         let mut out = [0; FQN_LEN];
         for (i, part) in self.parts().iter().enumerate() {
-            let mut hasher = XxHash32::default();
-            hasher.write(part.as_bytes());
-            out[i] = hasher.finish() as u32;
+            out[i] = HashedFqn::hash_part(part);
         }
 
         HashedFqn(out)
@@ -0,0 +1,90 @@
+//! Runs in its own process, the global context with an engine allocator can be initialized once.
+
+use std::{
+    alloc::{alloc, dealloc, Layout},
+    ptr::{copy_nonoverlapping, null_mut},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use ucore::{FMemory, FString, GlobalContext, TArray};
+
+static MALLOCS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+static FREES: AtomicUsize = AtomicUsize::new(0);
+
+// Size of the allocation is stored right before the returned buffer.
+const HEADER: usize = 16;
+
+fn layout(count: usize) -> Layout {
+    Layout::from_size_align(count + HEADER, HEADER).unwrap()
+}
+
+unsafe fn allocate(count: usize, alignment: u32) -> *mut u8 {
+    assert!(alignment as usize <= HEADER);
+    let ptr = alloc(layout(count));
+    ptr.cast::<usize>().write(count);
+    ptr.add(HEADER)
+}
+
+unsafe fn release(original: *mut u8) {
+    let ptr = original.sub(HEADER);
+    dealloc(ptr, layout(ptr.cast::<usize>().read()));
+}
+
+unsafe extern "C" fn malloc(count: usize, alignment: u32) -> *mut u8 {
+    MALLOCS.fetch_add(1, Ordering::SeqCst);
+    allocate(count, alignment)
+}
+
+unsafe extern "C" fn realloc(original: *mut u8, count: usize, alignment: u32) -> *mut u8 {
+    assert!(
+        !original.is_null(),
+        "New buffers must be allocated with malloc"
+    );
+    REALLOCS.fetch_add(1, Ordering::SeqCst);
+
+    let new = allocate(count, alignment);
+    let old = original.sub(HEADER).cast::<usize>().read();
+    copy_nonoverlapping(original, new, old.min(count));
+    release(original);
+
+    new
+}
+
+unsafe extern "C" fn free(original: *mut u8) {
+    FREES.fetch_add(1, Ordering::SeqCst);
+    release(original);
+}
+
+fn counts() -> [usize; 3] {
+    [&MALLOCS, &REALLOCS, &FREES].map(|c| c.load(Ordering::SeqCst))
+}
+
+#[test]
+fn test_engine_allocator() {
+    GlobalContext::new(null_mut(), null_mut())
+        .with_allocator(FMemory {
+            malloc,
+            realloc,
+            free,
+        })
+        .init();
+
+    let mut array = TArray::<u64>::new();
+    array.reserve(2);
+    assert_eq!(counts(), [1, 0, 0]);
+
+    // Grows from 4 to 8 to 16 elements.
+    for i in 0..10 {
+        array.push(i);
+    }
+    assert_eq!(counts(), [1, 2, 0]);
+    assert_eq!(array.as_slice(), (0..10).collect::<Vec<_>>());
+
+    drop(array);
+    assert_eq!(counts(), [1, 2, 1]);
+
+    let string = FString::from("Engine");
+    assert_eq!(string.to_string(), "Engine");
+    drop(string);
+    assert_eq!(counts(), [2, 2, 2]);
+}
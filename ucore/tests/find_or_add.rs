@@ -0,0 +1,34 @@
+//! Runs in its own process, the global context with an FName constructor can be initialized once.
+
+use std::{
+    ptr::null_mut,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use ucore::{FName, GlobalContext};
+
+static CONSTRUCTED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn constructor(this: *mut FName, _: *const u16, _: u32) -> *mut FName {
+    CONSTRUCTED.fetch_add(1, Ordering::SeqCst);
+    this.write(FName::from(100));
+    this
+}
+
+#[test]
+fn test_find_or_add() {
+    // Single block with the `None` entry, the length is stored in the upper bits of the header.
+    let block = Box::leak(vec![0u8; 0x10].into_boxed_slice());
+    block[..2].copy_from_slice(&(4u16 << 6).to_le_bytes());
+    block[2..6].copy_from_slice(b"None");
+    // Lock, CurrentBlock and CurrentByteCursor, Blocks.
+    let raw = Box::leak(Box::new([0, 6 << 32, block.as_ptr() as usize]));
+
+    GlobalContext::new(raw.as_mut_ptr().cast(), null_mut())
+        .with_fname_constructor(constructor)
+        .init();
+
+    assert_eq!(FName::find_or_add("None").unwrap().index(), 0);
+    assert_eq!(FName::find_or_add("Missing").unwrap().index(), 100);
+    assert_eq!(FName::find_or_add("Missing").unwrap().index(), 100);
+    assert_eq!(CONSTRUCTED.load(Ordering::SeqCst), 1);
+}